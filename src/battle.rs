@@ -2,25 +2,70 @@
 
 mod action;
 mod action_decision;
+mod ai;
+mod character_falls;
+mod data;
+mod effect;
+mod element;
+mod formula;
+mod growth;
+mod hit;
+mod intro;
+mod item;
+mod log;
+mod rhythm;
+mod rng;
+mod script;
+mod special_move;
 mod stat;
+mod status;
 mod turn;
+mod turn_order;
 mod turn_preparation;
+mod victory;
 
 use crate::battle::action_decision::{AllyActionRecord, CharacterTurnDecisionState};
-use crate::battle::stat::{ActorStats, Stat};
+use crate::battle::ai::{EnemyBrain, RandomTarget};
+use crate::battle::character_falls::CharacterFallsState;
+use crate::battle::element::Affinities;
+use crate::battle::formula::{DamageFormula, Mother2};
+use crate::battle::growth::Progression;
+use crate::battle::intro::IntroState;
+use crate::battle::item::ItemId;
+use crate::battle::log::BattleLog;
+use crate::battle::rhythm::{BeatClock, RhythmConfig, SyntheticClock, TrackInfo};
+use crate::battle::rng::BattleRng;
+use crate::battle::special_move::MoveId;
+use crate::battle::stat::{flee_chance, ActorStats, Stat};
+use crate::battle::status::Status;
 use crate::battle::turn::TurnUnrollState;
+use crate::battle::turn_order::{Priority, TurnOrderPolicy};
 use crate::battle::turn_preparation::TurnPreparationState;
+use crate::battle::victory::{BattleRewards, Rewards, VictoryState};
 use crate::meters::{InstantMeter, Meter, RollingMeter};
 use crate::Assets;
 use crate::Scene;
+use crate::SceneResult;
 use crate::Transition;
 use std::collections::VecDeque;
 use tetra::graphics::text::Text;
 use tetra::graphics::{self, Color, DrawParams};
+use tetra::input::{is_key_down, is_key_pressed, Key};
 use tetra::math::Vec2;
 use tetra::time;
 use tetra::Context;
 
+pub use crate::battle::data::{DataError, GameData, DATA_DIR};
+pub use crate::battle::formula::preset as damage_formula;
+pub use crate::battle::intro::Opening;
+pub use crate::battle::item::Inventory;
+
+// Rolling meter speed depending on the battle state.
+const DECISION_ROLL_RATE: f32 = 0.5;
+const UNROLL_ROLL_RATE: f32 = 2.;
+// Applied on top while UIAction::SlowRoll is held.
+const HELD_ROLL_RATE: f32 = 0.25;
+
 trait CharacterKoSignal {
     fn on_character_ko(&mut self, id: ActorIdentifier);
 }
@@ -30,6 +75,18 @@ pub struct Actor {
     pub hp: Meter,
     pub pp: Meter,
     pub stats: ActorStats,
+    pub moves: Vec<MoveId>,
+    pub statuses: Vec<Status>,
+    // Set for the turn the actor chose to guard.
+    pub guarding: bool,
+    // Only enemies think by themselves.
+    pub brain: Option<Box<dyn EnemyBrain>>,
+    // Key into the textures, see `Assets::sprite`. Characters aren't drawn.
+    pub sprite: Option<String>,
+    pub rewards: Rewards,
+    // Only characters level up.
+    pub progression: Option<Progression>,
+    pub affinities: Affinities,
 }
 
 impl Actor {
@@ -54,39 +111,81 @@ impl Actor {
                 defense: Stat::new(defense),
                 speed: Stat::new(speed),
                 iq: Stat::new(iq),
+                guts: Stat::new(0),
+                vitality: Stat::new(0),
+                luck: Stat::new(0),
             },
+            moves: vec![],
+            statuses: vec![],
+            guarding: false,
+            brain: Some(Box::new(RandomTarget)),
+            sprite: None,
+            rewards: Rewards::default(),
+            progression: None,
+            affinities: Affinities::default(),
         }
     }
 
-    // Characters use rolling meters.
-    fn character_from_stats(
-        name: &str,
-        hp: u16,
-        max_hp: u16,
-        pp: u16,
-        max_pp: u16,
-        offense: u16,
-        defense: u16,
-        speed: u16,
-        iq: u16,
-    ) -> Actor {
+    // Characters use rolling meters, their stats come from their level.
+    fn character(name: &str, progression: Progression) -> Actor {
+        let s = progression.stats();
         Actor {
             name: String::from(name),
-            hp: Meter::Rolling(RollingMeter::new(hp, max_hp)),
-            pp: Meter::Rolling(RollingMeter::new(pp, max_pp)),
+            hp: Meter::Rolling(RollingMeter::new(s.hp, s.hp)),
+            pp: Meter::Rolling(RollingMeter::new(s.pp, s.pp)),
             stats: ActorStats {
-                offense: Stat::new(offense),
-                defense: Stat::new(defense),
-                speed: Stat::new(speed),
-                iq: Stat::new(iq),
+                offense: Stat::new(s.offense),
+                defense: Stat::new(s.defense),
+                speed: Stat::new(s.speed),
+                iq: Stat::new(s.iq),
+                guts: Stat::new(s.guts),
+                vitality: Stat::new(s.vitality),
+                luck: Stat::new(s.luck),
             },
+            moves: progression.known_moves(),
+            statuses: vec![],
+            guarding: false,
+            brain: None,
+            sprite: None,
+            rewards: Rewards::default(),
+            progression: Some(progression),
+            affinities: Affinities::default(),
         }
     }
-    fn update_meters(&mut self, dt: f32) {
+
+    fn with_brain(mut self, brain: Box<dyn EnemyBrain>) -> Actor {
+        self.brain = Some(brain);
+        self
+    }
+
+    // Rolling HP only rolls towards 0 after a lethal hit, the character is still standing until
+    // the meter actually gets there and can be saved by a heal or the end of the battle.
+    fn take_damage(&mut self, amount: u16, log: &mut BattleLog) {
+        let was_mortally_wounded = self.hp.is_mortally_wounded();
+        self.hp.hit(amount);
+        if !was_mortally_wounded && self.hp.is_mortally_wounded() {
+            log.push(format!("{} took mortal damage!", self.name));
+        }
+    }
+
+    // Returns false (and spends nothing) if there isn't enough PP left.
+    fn try_spend_pp(&mut self, cost: u16) -> bool {
+        // What's left once the meter stops rolling, not what's shown right now.
+        if self.pp.target() < cost {
+            return false;
+        }
+        self.pp.hit(cost);
+        true
+    }
+
+    fn update_meters(&mut self, dt: f32, rate_multiplier: f32) {
+        let rate_multiplier = rate_multiplier * status::roll_rate_factor(self);
         if let Meter::Rolling(meter) = &mut self.hp {
+            meter.rate_multiplier = rate_multiplier;
             meter.update(dt)
         }
         if let Meter::Rolling(meter) = &mut self.pp {
+            meter.rate_multiplier = rate_multiplier;
             meter.update(dt)
         }
     }
@@ -94,7 +193,10 @@ impl Actor {
 
 // Turn structure idea
 
-enum UIAction {
+// Input commands fed to the battle. The engine only sees those, tetra's keyboard is mapped to them
+// in `read_keyboard_input`, so a fight can be driven by any other source (tests, replays...).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UIAction {
     Up,
     Down,
     Left,
@@ -103,6 +205,32 @@ enum UIAction {
     PageNext,
     Validate,
     Cancel, // Also works as back
+    // Sent every frame the button is held, rolling meters slow down.
+    SlowRoll,
+}
+
+fn read_keyboard_input(ctx: &Context) -> Vec<UIAction> {
+    const MAPPING: &[(Key, UIAction)] = &[
+        (Key::Up, UIAction::Up),
+        (Key::Down, UIAction::Down),
+        (Key::Left, UIAction::Left),
+        (Key::Right, UIAction::Right),
+        (Key::PageUp, UIAction::PagePrev),
+        (Key::PageDown, UIAction::PageNext),
+        (Key::Enter, UIAction::Validate),
+        (Key::Backspace, UIAction::Cancel),
+    ];
+    const HELD_MAPPING: &[(Key, UIAction)] = &[(Key::Space, UIAction::SlowRoll)];
+    MAPPING
+        .iter()
+        .filter(|(key, _)| is_key_pressed(ctx, *key))
+        .chain(
+            HELD_MAPPING
+                .iter()
+                .filter(|(key, _)| is_key_down(ctx, *key)),
+        )
+        .map(|(_, action)| *action)
+        .collect()
 }
 
 trait Drawable {
@@ -114,18 +242,20 @@ pub enum MacroBattleStates {
     TurnPreparation(TurnPreparationState),
     TurnUnroll(TurnUnrollState),
     // Out of the loop
-    Intro,
+    Intro(IntroState),
     Win,
     GameOver,
+    Fled,
     // Overriding state transitions
-    CharacterFalls,
+    CharacterFalls(CharacterFallsState),
 }
 
 // TODO Replace with Action instead?
+#[derive(Clone)]
 pub enum ActionType {
     Bash(Target),
-    Psi,
-    Item,
+    SpecialMove(MoveId, Target),
+    Item(ItemId, Target),
     Guard,
     // ???
 }
@@ -136,12 +266,21 @@ pub enum ActionType {
 
 // Scene?
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum Team {
     Ally,
     Enemy,
 }
 
+impl Team {
+    pub fn opposite(&self) -> Team {
+        match self {
+            Team::Ally => Team::Enemy,
+            Team::Enemy => Team::Ally,
+        }
+    }
+}
+
 type ActorIdentifier = (Team, usize);
 
 #[derive(Clone)]
@@ -171,47 +310,119 @@ impl Target {
 pub struct TurnAction {
     id_in_team: usize,
     team: Team,
+    priority: Priority,
+    // Rolled for this turn.
     speed: u16,
+    base_speed: u16,
+    // Last resort when everything else is equal.
+    tiebreak: u32,
 }
 
+// States only get the elapsed time and the frame's input commands, no tetra involved.
 pub trait BattleState {
-    fn update(scene: &mut BattleScene, ctx: &Context) -> BattleStateTransition;
+    fn update(scene: &mut BattleScene, dt: f32, input: &[UIAction]) -> BattleStateTransition;
 }
 
 pub type BattleStateTransition = Option<MacroBattleStates>;
 
+// How the battle ended, handed back to the scene below the battle.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BattleEnding {
+    Victory,
+    Defeat,
+    Fled,
+}
+
+pub struct BattleOutcome {
+    pub ending: BattleEnding,
+    // Only after a victory.
+    pub rewards: Option<BattleRewards>,
+    // Levels gained during the battle would be lost with the scene otherwise.
+    pub party: Vec<PartyMember>,
+}
+
+// A character as they leave the battle.
+pub struct PartyMember {
+    pub name: String,
+    pub progression: Progression,
+}
+
 pub struct BattleScene {
     pub allies: Vec<Actor>,
     pub enemies: Vec<Actor>,
+    pub inventory: Inventory,
+    pub log: BattleLog,
     // Test
     allies_actions: Vec<AllyActionRecord>,
+    // What each enemy picked when the turn started, by index.
+    enemies_actions: Vec<(usize, ActionType)>,
     turn_order: VecDeque<TurnAction>,
     // Stack?
     state: MacroBattleStates,
+    rng: BattleRng,
+    flee_attempts: u16,
+    turn: u32,
+    // Side getting a free opening turn, cleared once it's been played.
+    first_strike: Option<Team>,
+    // Set when entering the Win state.
+    victory: Option<VictoryState>,
+    // Keeps the beat of the battle track for combos.
+    beat: Box<dyn BeatClock>,
+    rhythm: RhythmConfig,
+    formula: Box<dyn DamageFormula>,
+    turn_order_policy: TurnOrderPolicy,
 }
 
 impl BattleScene {
-    pub fn dummy() -> BattleScene {
-        let allies = vec![
-            Actor::character_from_stats("One", 98, 98, 46, 46, 45, 22, 16, 10),
-            Actor::character_from_stats("Two", 115, 115, 0, 0, 35, 27, 12, 21),
-            Actor::character_from_stats("Three", 82, 82, 73, 73, 28, 29, 20, 16),
-            Actor::character_from_stats("Four", 67, 67, 0, 0, 32, 20, 9, 23),
-        ];
+    // The "dummy" encounter from the data files, with the whole party.
+    pub fn dummy(seed: u64) -> Result<BattleScene, DataError> {
+        GameData::load(DATA_DIR).and_then(|data| data.start_encounter("dummy", seed))
+    }
+
+    pub fn new(
+        allies: Vec<Actor>,
+        enemies: Vec<Actor>,
+        inventory: Inventory,
+        opening: Opening,
+        seed: u64,
+    ) -> BattleScene {
+        let mut rng = BattleRng::from_seed(seed);
+        let mut log = BattleLog::default();
+        let (intro, first_strike) =
+            IntroState::start(&allies, &enemies, opening, &mut rng, &mut log);
         BattleScene {
-            enemies: vec![
-                Actor::enemy_from_stats("Robot", 53, 53, 0, 0, 35, 10, 17, 8),
-                Actor::enemy_from_stats("Robot", 53, 53, 0, 0, 35, 10, 17, 8),
-            ],
+            enemies,
+            inventory,
+            log,
             allies_actions: vec![],
+            enemies_actions: vec![],
             turn_order: VecDeque::new(),
-            state: MacroBattleStates::CharacterTurnDecision(
-                CharacterTurnDecisionState::new_turn(&allies).unwrap(),
-            ),
-            allies: allies,
+            state: MacroBattleStates::Intro(intro),
+            allies,
+            rng,
+            flee_attempts: 0,
+            turn: 0,
+            first_strike,
+            victory: None,
+            beat: Box::new(SyntheticClock::new(TrackInfo::default())),
+            rhythm: RhythmConfig::default(),
+            formula: Box::new(Mother2::default()),
+            turn_order_policy: TurnOrderPolicy::default(),
         }
     }
 
+    // There's no audio yet, the track only gives the tempo to a synthetic clock.
+    pub fn with_music(mut self, track: TrackInfo, rhythm: RhythmConfig) -> BattleScene {
+        self.beat = Box::new(SyntheticClock::new(track));
+        self.rhythm = rhythm;
+        self
+    }
+
+    pub fn with_formula(mut self, formula: Box<dyn DamageFormula>) -> BattleScene {
+        self.formula = formula;
+        self
+    }
+
     fn compute_hud_table(title: &str, actors: &[Actor]) -> String {
         let mut actor_summary = {
             if actors.is_empty() {
@@ -224,14 +435,51 @@ impl BattleScene {
             let (hp, max_hp) = actor.hp.current_and_max();
             let (pp, max_pp) = actor.pp.current_and_max();
             let actor_line = format!(
-                "{:8}|\n {:3}/{:3}|{:3}/{:3}\n",
-                actor.name, hp, max_hp, pp, max_pp,
+                "{:8}|{}\n {:3}/{:3}|{:3}/{:3}\n",
+                actor.name,
+                BattleScene::hud_status(actor),
+                hp,
+                max_hp,
+                pp,
+                max_pp,
             );
             actor_summary.push_str(&actor_line);
         }
         actor_summary
     }
 
+    fn hud_status(actor: &Actor) -> String {
+        if actor.hp.is_mortally_wounded() {
+            format!("DYING {}", status::short_description(actor))
+        } else {
+            status::short_description(actor)
+        }
+    }
+
+    // Standing enemies in a row above the state text, scaled down to the same height.
+    fn draw_enemy_sprites(&self, ctx: &mut Context, assets: &Assets) {
+        const HEIGHT: f32 = 96.;
+        const BOTTOM: f32 = 352.;
+        let slot_width = 640. / (self.enemies.len() + 1) as f32;
+        for (i, enemy) in self.enemies.iter().enumerate() {
+            if enemy.hp.current_and_max().0 == 0 {
+                continue;
+            }
+            let texture = match enemy.sprite.as_deref().and_then(|name| assets.sprite(name)) {
+                Some(texture) => texture,
+                None => continue,
+            };
+            let (width, height) = (texture.width() as f32, texture.height() as f32);
+            texture.draw(
+                ctx,
+                DrawParams::new()
+                    .position(Vec2::new(slot_width * (i + 1) as f32, BOTTOM))
+                    .origin(Vec2::new(width / 2., height))
+                    .scale(Vec2::broadcast(HEIGHT / height)),
+            );
+        }
+    }
+
     fn draw_debug_hud(&self, ctx: &mut Context, assets: &Assets) {
         let character_summary = BattleScene::compute_hud_table("Characters", &self.allies);
         let mut text = Text::new(character_summary, assets.headupdaisy.clone());
@@ -250,10 +498,50 @@ impl BattleScene {
                 .color(Color::rgb8(0xeb, 0xdb, 0xb2))
                 .position(Vec2::new(336., 16.)),
         );
+
+        let order: Vec<&str> = turn_order::preview(self)
+            .iter()
+            .map(|(team, id)| match team {
+                Team::Ally => self.allies[*id].name.as_str(),
+                Team::Enemy => self.enemies[*id].name.as_str(),
+            })
+            .collect();
+        let mut order_text = Text::new(
+            format!("Order: {}", order.join(" > ")),
+            assets.headupdaisy.clone(),
+        );
+        order_text.draw(
+            ctx,
+            DrawParams::new()
+                .color(Color::rgb8(0xa8, 0x99, 0x84))
+                .position(Vec2::new(16., 240.)),
+        );
+
+        let mut log_text = Text::new(
+            self.log.last_lines(3).join("\n"),
+            assets.headupdaisy.clone(),
+        );
+        log_text.draw(
+            ctx,
+            DrawParams::new()
+                .color(Color::rgb8(0xa8, 0x99, 0x84))
+                .position(Vec2::new(16., 272.)),
+        );
+
+        let mut formula_text = Text::new(
+            format!("Formula: {}", self.formula.name()),
+            assets.headupdaisy.clone(),
+        );
+        formula_text.draw(
+            ctx,
+            DrawParams::new()
+                .color(Color::rgb8(0xa8, 0x99, 0x84))
+                .position(Vec2::new(336., 360.)),
+        );
     }
 
     pub fn all_ko(arr: &[Actor]) -> bool {
-        arr.iter().all(|e| e.hp.current_and_max().0 == 0)
+        arr.iter().all(|e| e.hp.is_ko())
     }
 
     pub fn end_of_fight(&self) -> bool {
@@ -269,57 +557,192 @@ impl BattleScene {
         None
     }
 
+    pub fn outcome(&self) -> Option<BattleOutcome> {
+        let ending = match self.state {
+            MacroBattleStates::Win => BattleEnding::Victory,
+            MacroBattleStates::GameOver => BattleEnding::Defeat,
+            MacroBattleStates::Fled => BattleEnding::Fled,
+            _ => return None,
+        };
+        Some(BattleOutcome {
+            ending,
+            rewards: self.victory.as_ref().map(|v| v.rewards.clone()),
+            party: self.party(),
+        })
+    }
+
+    pub fn party(&self) -> Vec<PartyMember> {
+        self.allies
+            .iter()
+            .filter_map(|actor| {
+                actor.progression.as_ref().map(|progression| PartyMember {
+                    name: actor.name.clone(),
+                    progression: progression.clone(),
+                })
+            })
+            .collect()
+    }
+
+    fn average_speed(actors: &[Actor]) -> u16 {
+        let speeds: Vec<u32> = actors
+            .iter()
+            .filter(|a| a.hp.current_and_max().0 > 0)
+            .map(|a| u32::from(a.stats.speed.multiplied()))
+            .collect();
+        if speeds.is_empty() {
+            return 0;
+        }
+        (speeds.iter().sum::<u32>() / speeds.len() as u32) as u16
+    }
+
+    // Party-wide and resolved as soon as it's picked. Failing costs the party their turn.
+    fn try_to_flee(&mut self) -> MacroBattleStates {
+        let chance = flee_chance(
+            BattleScene::average_speed(&self.allies),
+            BattleScene::average_speed(&self.enemies),
+            self.flee_attempts,
+        );
+        self.flee_attempts += 1;
+        if self.rng.chance(chance) {
+            self.log.push(String::from("The party got away safely!"));
+            MacroBattleStates::Fled
+        } else {
+            self.log.push(String::from("The party couldn't get away!"));
+            self.allies_actions.clear();
+            MacroBattleStates::TurnPreparation(TurnPreparationState {})
+        }
+    }
+
+    // The party gets time to think, less so when things happen.
+    fn roll_rate(&self, input: &[UIAction]) -> f32 {
+        let state_rate = match self.state {
+            MacroBattleStates::CharacterTurnDecision(_) => DECISION_ROLL_RATE,
+            MacroBattleStates::TurnUnroll(_) => UNROLL_ROLL_RATE,
+            _ => 1.,
+        };
+        if input.contains(&UIAction::SlowRoll) {
+            state_rate * HELD_ROLL_RATE
+        } else {
+            state_rate
+        }
+    }
+
     // Also signals K.O. event. Might be good to track in another function instead.
-    fn update_actor_meters(&mut self, dt: f32) {
+    fn update_actor_meters(&mut self, dt: f32, input: &[UIAction]) {
         if self.end_of_fight() {
             return;
         }
+        let rate = self.roll_rate(input);
         for enemy in self.enemies.iter_mut() {
-            enemy.update_meters(dt);
+            enemy.update_meters(dt, rate);
         }
+        let mut fallen_during_unroll = vec![];
         for (id, character) in self.allies.iter_mut().enumerate() {
             let previous_hp = character.hp.current_and_max().0;
-            character.update_meters(dt);
-            if previous_hp > 0 && character.hp.current_and_max().0 == 0 {
-                println!("{} is K.O.", character.name);
+            character.update_meters(dt, rate);
+            if previous_hp > 0 && character.hp.is_ko() {
+                self.log.push(format!("{} is K.O.", character.name));
 
                 // TODO finish death signaling
                 match &mut self.state {
                     MacroBattleStates::CharacterTurnDecision(decision_state) => {
+                        // Whatever they had picked earlier this turn won't happen.
+                        self.allies_actions.retain(|record| record.id_in_team != id);
                         decision_state.on_character_ko((Team::Ally, id));
                     }
-                    MacroBattleStates::TurnUnroll(_) => {
-                        // TODO signal ko during unroll
+                    MacroBattleStates::TurnUnroll(unroll_state) => {
+                        // Their queued action won't happen either.
+                        self.turn_order.retain(|action| {
+                            !(action.team == Team::Ally && action.id_in_team == id)
+                        });
+                        unroll_state.on_character_ko((Team::Ally, id));
+                        fallen_during_unroll.push(id);
+                    }
+                    MacroBattleStates::CharacterFalls(falls_state) => {
+                        self.turn_order.retain(|action| {
+                            !(action.team == Team::Ally && action.id_in_team == id)
+                        });
+                        falls_state.on_character_ko((Team::Ally, id));
                     }
                     _ => (),
                 }
             }
         }
+
+        if !fallen_during_unroll.is_empty() {
+            // The unroll state is put aside until the interruption is over.
+            if let MacroBattleStates::TurnUnroll(unroll_state) = &mut self.state {
+                let interrupted = unroll_state.take();
+                self.state = MacroBattleStates::CharacterFalls(CharacterFallsState::new(
+                    fallen_during_unroll,
+                    interrupted,
+                ));
+            }
+        }
     }
 }
 
-impl Scene for BattleScene {
-    fn update(&mut self, ctx: &mut Context, _assets: &Assets) -> tetra::Result<Transition> {
-        let dt = time::get_delta_time(ctx).as_secs_f32();
-        self.update_actor_meters(dt);
+impl BattleScene {
+    // Headless entry point: advances the whole battle by `dt` seconds with the given inputs.
+    // Returns the outcome once the battle is over and its ending screen has been validated.
+    pub fn step(&mut self, dt: f32, input: &[UIAction]) -> Option<BattleOutcome> {
+        if let Some(outcome) = self.outcome() {
+            if let Some(victory) = &mut self.victory {
+                if !victory.is_done() {
+                    victory.update(input);
+                    return None;
+                }
+            }
+            if input.contains(&UIAction::Validate) {
+                return Some(outcome);
+            }
+            return None;
+        }
+        self.update_actor_meters(dt, input);
+        self.beat.advance(dt);
 
         let transition = match &self.state {
             MacroBattleStates::CharacterTurnDecision(_) => {
-                CharacterTurnDecisionState::update(self, ctx)
+                CharacterTurnDecisionState::update(self, dt, input)
             }
-            MacroBattleStates::TurnPreparation(_) => TurnPreparationState::update(self, ctx),
-            MacroBattleStates::TurnUnroll(_) => TurnUnrollState::update(self, ctx),
+            MacroBattleStates::TurnPreparation(_) => TurnPreparationState::update(self, dt, input),
+            MacroBattleStates::TurnUnroll(_) => TurnUnrollState::update(self, dt, input),
+            MacroBattleStates::CharacterFalls(_) => CharacterFallsState::update(self, dt, input),
+            MacroBattleStates::Intro(_) => IntroState::update(self, dt, input),
             _ => None,
         };
         if let Some(next_state) = transition {
             self.state = next_state;
+            // Whoever was still rolling towards 0 when the battle ended made it. Buffs and debuffs
+            // end with the battle.
+            if self.outcome().is_some() {
+                for character in self.allies.iter_mut() {
+                    character.hp.settle();
+                    character.pp.settle();
+                    character.stats.reset_modifiers();
+                }
+            }
+            if let MacroBattleStates::Win = self.state {
+                self.victory = Some(VictoryState::new(self));
+            }
         }
+        None
+    }
+}
 
-        Ok(Transition::None)
+impl Scene for BattleScene {
+    fn update(&mut self, ctx: &mut Context, _assets: &Assets) -> tetra::Result<Transition> {
+        let dt = time::get_delta_time(ctx).as_secs_f32();
+        let input = read_keyboard_input(ctx);
+        match self.step(dt, &input) {
+            Some(outcome) => Ok(Transition::PopWith(SceneResult::Battle(outcome))),
+            None => Ok(Transition::None),
+        }
     }
 
     fn draw(&mut self, ctx: &mut Context, assets: &Assets) -> tetra::Result<()> {
         graphics::clear(ctx, Color::rgb8(0x28, 0x28, 0x28));
+        self.draw_enemy_sprites(ctx, assets);
         self.draw_debug_hud(ctx, assets);
 
         match &self.state {
@@ -328,8 +751,15 @@ impl Scene for BattleScene {
             }
             MacroBattleStates::TurnPreparation(_) => TurnPreparationState::draw(&self, ctx, assets),
             MacroBattleStates::TurnUnroll(_) => TurnUnrollState::draw(&self, ctx, assets),
+            MacroBattleStates::CharacterFalls(_) => CharacterFallsState::draw(self, ctx, assets),
+            MacroBattleStates::Intro(_) => IntroState::draw(self, ctx, assets),
             MacroBattleStates::Win => {
-                let mut debug_text = Text::new("--Victory!--\n", assets.headupdaisy.clone());
+                if let Some(victory) = &self.victory {
+                    victory.draw(ctx, assets);
+                }
+            }
+            MacroBattleStates::GameOver => {
+                let mut debug_text = Text::new("--Game over!--\n", assets.headupdaisy.clone());
                 debug_text.draw(
                     ctx,
                     DrawParams::new()
@@ -337,8 +767,9 @@ impl Scene for BattleScene {
                         .position(Vec2::new(16., 360.)),
                 );
             }
-            MacroBattleStates::GameOver => {
-                let mut debug_text = Text::new("--Game over!--\n", assets.headupdaisy.clone());
+            MacroBattleStates::Fled => {
+                let mut debug_text =
+                    Text::new("--Got away safely!--\n", assets.headupdaisy.clone());
                 debug_text.draw(
                     ctx,
                     DrawParams::new()
@@ -346,8 +777,53 @@ impl Scene for BattleScene {
                         .position(Vec2::new(16., 360.)),
                 );
             }
-            _ => (),
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use UIAction::*;
+
+    const DT: f32 = 1. / 60.;
+    // Ten minutes of game time, plenty for the dummy fight.
+    const MAX_FRAMES: usize = 60 * 60 * 10;
+
+    // Bash only. Left keeps the menu on Bash and moves the target away from a K.O. robot.
+    const SCRIPT: &[&[UIAction]] = &[&[Validate], &[], &[Left], &[Validate], &[]];
+
+    // Plays the dummy fight headless, the script is looped one entry per frame.
+    // Returns how it ended and everything that happened, what a replay has to reproduce.
+    fn play(seed: u64, script: &[&[UIAction]]) -> (BattleEnding, Vec<String>) {
+        let mut scene = BattleScene::dummy(seed).unwrap_or_else(|err| panic!("{}", err));
+        for frame in 0..MAX_FRAMES {
+            if let Some(outcome) = scene.step(DT, script[frame % script.len()]) {
+                return (outcome.ending, scene.log.last_lines(usize::MAX).to_vec());
+            }
+        }
+        panic!(
+            "The fight was still going after {} frames:\n{}",
+            MAX_FRAMES,
+            scene.log.last_lines(5).join("\n")
+        );
+    }
+
+    #[test]
+    fn bashing_through_the_dummy_fight() {
+        let (ending, log) = play(42, SCRIPT);
+        assert_eq!(ending, BattleEnding::Victory);
+        assert_eq!(log.last().unwrap(), "The enemy dropped a Hamburger!");
+    }
+
+    #[test]
+    fn same_seed_same_fight() {
+        assert_eq!(play(42, SCRIPT), play(42, SCRIPT));
+    }
+
+    #[test]
+    fn different_seed_different_fight() {
+        assert_ne!(play(42, SCRIPT).1, play(7, SCRIPT).1);
+    }
+}
@@ -1,66 +1,394 @@
-use crate::battle::stat::damage;
-use crate::battle::stat::ActorStats;
+use crate::battle::effect::Effect;
+use crate::battle::element::{self, Element, ElementalDamage};
+use crate::battle::formula::{self, DamageFormula};
+use crate::battle::hit::{self, Accuracy, HitOutcome};
+use crate::battle::item::{self, ItemId};
+use crate::battle::log::BattleLog;
+use crate::battle::rhythm::{BeatInput, Combo, ComboStep};
+use crate::battle::rng::BattleRng;
+use crate::battle::special_move::{self, MoveId, TargetRule};
+use crate::battle::stat::{self, ActorStats};
+use crate::battle::status::Status;
 use crate::battle::turn::SubStateTransition;
 use crate::battle::turn::TurnSubState;
 use crate::battle::Actor;
 use crate::timer::Timer;
-use tetra::Context;
+
+// Snapshot of whoever acts, taken right before the action happens.
+#[derive(Clone)]
+pub struct Caster {
+    pub name: String,
+    pub stats: ActorStats,
+    pub statuses: Vec<Status>,
+}
+
+impl Caster {
+    pub fn snapshot(actor: &Actor) -> Caster {
+        Caster {
+            name: actor.name.clone(),
+            stats: actor.stats.clone(),
+            statuses: actor.statuses.clone(),
+        }
+    }
+}
+
+// Battle-wide things an action might need besides the actors.
+pub struct ActionContext<'a> {
+    pub beat: BeatInput,
+    pub formula: &'a dyn DamageFormula,
+}
 
 pub trait Action {
     fn go(
         &mut self,
-        caster_stats: &ActorStats,
+        caster: &Caster,
         targets: &mut [Actor],
-        ctx: &Context,
+        dt: f32,
+        context: &ActionContext,
+        rng: &mut BattleRng,
+        log: &mut BattleLog,
     ) -> SubStateTransition;
     // Cancel due to K.O.
     fn cancel(&mut self);
+    // What happens when the single target went K.O. before the action happened.
+    fn on_ko_target(&self) -> KoTargetRule;
+    // How it went for each target, in order, once the action happened. Only the turn reports
+    // misses and dodges.
+    fn take_hits(&mut self) -> Vec<HitOutcome> {
+        vec![]
+    }
+}
+
+pub enum KoTargetRule {
+    // Go for another living member of the same team.
+    Retarget,
+    // The action is wasted.
+    Fizzle,
+    // Doesn't matter (or the action is meant for K.O. actors).
+    Ignore,
+}
+
+// Attacks find someone else to hit, help is wasted.
+fn ko_target_rule(target: TargetRule, effect: &Effect) -> KoTargetRule {
+    if effect.aims_ko_actors() {
+        KoTargetRule::Ignore
+    } else if let TargetRule::SingleEnemy = target {
+        KoTargetRule::Retarget
+    } else {
+        KoTargetRule::Fizzle
+    }
 }
 
-// Bash attack
-// (Really don't want to mess with the music-based beat-down for now)
+const BASH_ACCURACY: Accuracy = Accuracy::Physical(0.95);
+
+// Bash attack. Characters can keep hitting by pressing on the beat, see rhythm.rs.
 pub struct Bash {
     time: Timer,
     cancelled: bool,
+    hits: Vec<HitOutcome>,
+    can_combo: bool,
+    // Set after the first hit.
+    combo: Option<Combo>,
 }
 
 impl Bash {
-    pub fn new() -> Bash {
+    pub fn new(can_combo: bool) -> Bash {
         Bash {
             time: Timer::new(1.0),
             cancelled: false,
+            hits: vec![],
+            can_combo,
+            combo: None,
         }
     }
 }
 
-fn physical_damage(offense: u16, attack_level: u16, targets: &mut [Actor]) {
+fn physical_damage(
+    caster: &Caster,
+    attack_level: u16,
+    targets: &mut [Actor],
+    context: &ActionContext,
+    rng: &mut BattleRng,
+    log: &mut BattleLog,
+) -> Vec<HitOutcome> {
+    let mut hits = vec![];
     for target in targets.iter_mut() {
-        let dmg = damage(offense, attack_level, target.stats.defense.multiplied());
-        target.hp.hit(dmg);
+        let outcome = hit::resolve(BASH_ACCURACY, caster, target, rng);
+        hits.push(outcome);
+        if !outcome.connects() {
+            continue;
+        }
+        // Critical hits go right through the defense.
+        let critical = rng.chance(stat::critical_chance(caster.stats.guts.multiplied()));
+        let defense = if critical {
+            log.push(String::from("SMAAAASH!!"));
+            0
+        } else {
+            target.stats.defense.multiplied()
+        };
+        let dmg = formula::roll(
+            context.formula,
+            caster.stats.offense.multiplied(),
+            attack_level,
+            defense,
+            rng,
+        );
+        let mut dmg = match element::resolve(Element::Physical, dmg, target, log) {
+            ElementalDamage::Damage(dmg) => dmg,
+            ElementalDamage::Heal(amount) => {
+                target.hp.heal(amount);
+                log.push(format!("{} recovered {} HP.", target.name, amount));
+                continue;
+            }
+        };
+        if target.guarding {
+            dmg /= 2;
+            log.push(format!("{} guarded.", target.name));
+        }
+        let left = target.hp.target();
+        if dmg >= left
+            && left > 1
+            && rng.chance(stat::survive_chance(target.stats.guts.multiplied()))
+        {
+            dmg = left - 1;
+            log.push(format!("{} held on with sheer guts!", target.name));
+        }
+        log.push(format!("{} took {} damage.", target.name, dmg));
+        target.take_damage(dmg, log);
     }
+    hits
 }
 
 impl Action for Bash {
     fn go(
         &mut self,
-        caster_stats: &ActorStats,
+        caster: &Caster,
         targets: &mut [Actor],
-        ctx: &Context,
+        dt: f32,
+        context: &ActionContext,
+        rng: &mut BattleRng,
+        log: &mut BattleLog,
     ) -> SubStateTransition {
         use SubStateTransition::*;
         if self.cancelled {
             return NextSubState(TurnSubState::NextAction);
         }
-        self.time.tetra_tick(ctx);
+        if let Some(combo) = &mut self.combo {
+            return match combo.update(&context.beat) {
+                ComboStep::Wait => None,
+                ComboStep::Hit => {
+                    self.hits = physical_damage(caster, 1, targets, context, rng, log);
+                    // No point in going on, whoever was hit is going down.
+                    if targets.iter().all(|t| t.hp.target() == 0) {
+                        log.push(format!("{} hit combo!", combo.hits()));
+                        return NextSubState(TurnSubState::NextAction);
+                    }
+                    None
+                }
+                ComboStep::Over => {
+                    if combo.hits() > 1 {
+                        log.push(format!("{} hit combo!", combo.hits()));
+                    }
+                    NextSubState(TurnSubState::NextAction)
+                }
+            };
+        }
+
+        self.time.tick(dt);
         if !self.time.done() {
             return None;
         }
+        self.hits = physical_damage(caster, 1, targets, context, rng, log);
+        if self.can_combo && self.hits.iter().any(|hit| hit.connects()) {
+            self.combo = Some(Combo::start(&context.beat));
+            return None;
+        }
+        NextSubState(TurnSubState::NextAction)
+    }
 
-        physical_damage(caster_stats.offense.multiplied(), 1, targets);
+    fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    fn on_ko_target(&self) -> KoTargetRule {
+        KoTargetRule::Retarget
+    }
+
+    fn take_hits(&mut self) -> Vec<HitOutcome> {
+        std::mem::take(&mut self.hits)
+    }
+}
+
+// Special moves. PP is paid before announcing the move, see TurnUnrollState.
+pub struct SpecialMoveAction {
+    time: Timer,
+    move_id: MoveId,
+    cancelled: bool,
+    hits: Vec<HitOutcome>,
+}
+
+impl SpecialMoveAction {
+    pub fn new(move_id: MoveId) -> SpecialMoveAction {
+        SpecialMoveAction {
+            time: Timer::new(1.0),
+            move_id,
+            cancelled: false,
+            hits: vec![],
+        }
+    }
+}
+
+impl Action for SpecialMoveAction {
+    fn go(
+        &mut self,
+        caster: &Caster,
+        targets: &mut [Actor],
+        dt: f32,
+        _context: &ActionContext,
+        rng: &mut BattleRng,
+        log: &mut BattleLog,
+    ) -> SubStateTransition {
+        use SubStateTransition::*;
+        if self.cancelled {
+            return NextSubState(TurnSubState::NextAction);
+        }
+        self.time.tick(dt);
+        if !self.time.done() {
+            return None;
+        }
+
+        let special_move = special_move::get(self.move_id);
+        for target in targets.iter_mut() {
+            let outcome = hit::resolve(special_move.accuracy, caster, target, rng);
+            self.hits.push(outcome);
+            if outcome.connects() {
+                special_move
+                    .effect
+                    .apply(special_move.element, target, rng, log);
+            }
+        }
         NextSubState(TurnSubState::NextAction)
     }
 
     fn cancel(&mut self) {
         self.cancelled = true;
     }
+
+    fn on_ko_target(&self) -> KoTargetRule {
+        let special_move = special_move::get(self.move_id);
+        ko_target_rule(special_move.target, &special_move.effect)
+    }
+
+    fn take_hits(&mut self) -> Vec<HitOutcome> {
+        std::mem::take(&mut self.hits)
+    }
+}
+
+// Items. Like PP, the item is taken from the inventory before being announced.
+pub struct ItemAction {
+    time: Timer,
+    item_id: ItemId,
+    cancelled: bool,
+}
+
+impl ItemAction {
+    pub fn new(item_id: ItemId) -> ItemAction {
+        ItemAction {
+            time: Timer::new(1.0),
+            item_id,
+            cancelled: false,
+        }
+    }
+}
+
+impl Action for ItemAction {
+    fn go(
+        &mut self,
+        _caster: &Caster,
+        targets: &mut [Actor],
+        dt: f32,
+        _context: &ActionContext,
+        rng: &mut BattleRng,
+        log: &mut BattleLog,
+    ) -> SubStateTransition {
+        use SubStateTransition::*;
+        if self.cancelled {
+            return NextSubState(TurnSubState::NextAction);
+        }
+        self.time.tick(dt);
+        if !self.time.done() {
+            return None;
+        }
+
+        let item = item::get(self.item_id);
+        for target in targets.iter_mut() {
+            item.effect.apply(item.element, target, rng, log);
+        }
+        NextSubState(TurnSubState::NextAction)
+    }
+
+    fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    fn on_ko_target(&self) -> KoTargetRule {
+        let item = item::get(self.item_id);
+        ko_target_rule(item.target, &item.effect)
+    }
+}
+
+// Guarding itself happens at the start of the turn (see TurnPreparationState), this is only the
+// moment the actor catches their breath.
+pub struct Guard {
+    time: Timer,
+    cancelled: bool,
+}
+
+impl Guard {
+    pub fn new() -> Guard {
+        Guard {
+            time: Timer::new(0.5),
+            cancelled: false,
+        }
+    }
+}
+
+impl Action for Guard {
+    fn go(
+        &mut self,
+        _caster: &Caster,
+        targets: &mut [Actor],
+        dt: f32,
+        _context: &ActionContext,
+        _rng: &mut BattleRng,
+        log: &mut BattleLog,
+    ) -> SubStateTransition {
+        use SubStateTransition::*;
+        if self.cancelled {
+            return NextSubState(TurnSubState::NextAction);
+        }
+        self.time.tick(dt);
+        if !self.time.done() {
+            return None;
+        }
+
+        // The only target is the guarding actor.
+        for target in targets.iter_mut() {
+            let (_, max_pp) = target.pp.current_and_max();
+            let recovered = max_pp / 20;
+            if recovered > 0 {
+                target.pp.heal(recovered);
+                log.push(format!("{} recovered {} PP.", target.name, recovered));
+            }
+        }
+        NextSubState(TurnSubState::NextAction)
+    }
+
+    fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    fn on_ko_target(&self) -> KoTargetRule {
+        KoTargetRule::Ignore
+    }
 }
@@ -1,4 +1,8 @@
 // Engine states?
+use crate::battle::element;
+use crate::battle::item::{self, Inventory, ItemId};
+use crate::battle::special_move::{self, MoveId};
+use crate::battle::status;
 use crate::battle::turn_preparation::TurnPreparationState;
 use crate::battle::ActionType;
 use crate::battle::Actor;
@@ -7,12 +11,12 @@ use crate::battle::CharacterKoSignal;
 use crate::battle::MacroBattleStates;
 use crate::battle::Target;
 use crate::battle::Team;
+use crate::battle::UIAction;
 use crate::battle::{BattleState, BattleStateTransition};
 use crate::Assets;
 use crate::BattleScene;
 use tetra::graphics::text::Text;
 use tetra::graphics::{Color, DrawParams};
-use tetra::input::{is_key_pressed, Key};
 use tetra::math::Vec2;
 use tetra::Context;
 
@@ -26,22 +30,20 @@ pub enum CharacterTurnDecisionState {
     Menu(Menu),
     Bash(BashTargetSelection),
     // TODO Special Move instead of SpecialMove (1am brain)
-    // TODO Special move selection before target selection
+    SpecialMoveSelection(SpecialMoveSelection),
     SpecialMove(SpecialMoveTargetSelection),
-    //
-    // SpecialMoveSelection(u8),
-    // SpecialTargetSelection,
-    //
-    // ItemSelection,
-    // ItemTargetSelection,
-    //
+    ItemSelection(ItemSelection),
+    Item(ItemTargetSelection),
 }
 
 impl CharacterKoSignal for CharacterTurnDecisionState {
     fn on_character_ko(&mut self, id: ActorIdentifier) {
         let shared_state: &mut Breadcrumbs = match self {
             CharacterTurnDecisionState::Bash(bash_state) => &mut bash_state.shared,
+            CharacterTurnDecisionState::SpecialMoveSelection(list_state) => &mut list_state.shared,
             CharacterTurnDecisionState::SpecialMove(move_state) => &mut move_state.shared,
+            CharacterTurnDecisionState::ItemSelection(list_state) => &mut list_state.shared,
+            CharacterTurnDecisionState::Item(item_state) => &mut item_state.shared,
             CharacterTurnDecisionState::Menu(menu_state) => &mut menu_state.shared,
         };
         if shared_state.current_character == id.1 {
@@ -51,32 +53,34 @@ impl CharacterKoSignal for CharacterTurnDecisionState {
 }
 
 impl CharacterTurnDecisionState {
-    pub fn new_turn(characters: &[Actor]) -> Option<CharacterTurnDecisionState> {
-        if let Some((i, _)) = characters
+    // Starts with the first character able to act. If nobody can (everyone's asleep), the
+    // turn goes on without the party.
+    pub fn new_turn(characters: &[Actor]) -> MacroBattleStates {
+        CharacterTurnDecisionState::first_able_character(characters, 0)
+    }
+
+    fn next_character(characters: &[Actor], current: usize) -> MacroBattleStates {
+        CharacterTurnDecisionState::first_able_character(characters, current + 1)
+    }
+
+    fn first_able_character(characters: &[Actor], from: usize) -> MacroBattleStates {
+        match characters
             .iter()
             .enumerate()
-            .find(|(_, c)| c.hp.current_and_max().0 > 0)
+            .skip(from)
+            .find(|(_, c)| can_choose_action(c))
         {
-            return Some(CharacterTurnDecisionState::Menu(Menu {
-                shared: Breadcrumbs {
-                    current_character: i,
-                    current_item: 0,
-                    ko_signal: false,
-                },
-            }));
+            Some((i, _)) => {
+                MacroBattleStates::CharacterTurnDecision(CharacterTurnDecisionState::Menu(Menu {
+                    shared: Breadcrumbs {
+                        current_character: i,
+                        current_item: 0,
+                        ko_signal: false,
+                    },
+                }))
+            }
+            None => MacroBattleStates::TurnPreparation(TurnPreparationState {}),
         }
-        None
-    }
-
-    fn next_character(_characters: &[Actor], current: usize) -> MacroBattleStates {
-        MacroBattleStates::CharacterTurnDecision(CharacterTurnDecisionState::Menu(Menu {
-            // TODO Determine end of action (here or somewhere else, I guess)
-            shared: Breadcrumbs {
-                current_character: current + 1,
-                current_item: 0,
-                ko_signal: false,
-            },
-        }))
     }
 
     pub fn draw(scene: &BattleScene, ctx: &mut Context, assets: &Assets) {
@@ -85,6 +89,11 @@ impl CharacterTurnDecisionState {
                 CharacterTurnDecisionState::Menu(menu) => {
                     menu.draw(ctx, assets, &scene.allies[menu.shared.current_character])
                 }
+                CharacterTurnDecisionState::SpecialMoveSelection(list_state) => list_state.draw(
+                    ctx,
+                    assets,
+                    &scene.allies[list_state.shared.current_character],
+                ),
                 CharacterTurnDecisionState::SpecialMove(move_state) => move_state.draw(
                     ctx,
                     assets,
@@ -97,31 +106,51 @@ impl CharacterTurnDecisionState {
                 CharacterTurnDecisionState::Bash(bash) => {
                     bash.draw(ctx, assets, &scene.enemies);
                 }
+                CharacterTurnDecisionState::ItemSelection(list_state) => {
+                    list_state.draw(ctx, assets, &scene.inventory)
+                }
+                CharacterTurnDecisionState::Item(item_state) => item_state.draw(
+                    ctx,
+                    assets,
+                    get_possible_targets(
+                        item_state.current_target(),
+                        &scene.allies,
+                        &scene.enemies,
+                    ),
+                ),
             }
         }
     }
 }
 
 impl BattleState for CharacterTurnDecisionState {
-    fn update(scene: &mut BattleScene, ctx: &Context) -> BattleStateTransition {
+    fn update(scene: &mut BattleScene, _dt: f32, input: &[UIAction]) -> BattleStateTransition {
         if let MacroBattleStates::CharacterTurnDecision(sub_state) = &mut scene.state {
             let result = match sub_state {
-                CharacterTurnDecisionState::Menu(menu) => menu.update(&scene.allies, ctx),
+                CharacterTurnDecisionState::Menu(menu) => {
+                    menu.update(&scene.allies, &scene.inventory, input)
+                }
                 CharacterTurnDecisionState::Bash(bash) => {
-                    bash.update(ctx, &scene.allies, &scene.enemies)
+                    bash.update(input, &scene.allies, &scene.enemies)
+                }
+                CharacterTurnDecisionState::SpecialMoveSelection(list_state) => {
+                    list_state.update(&scene.allies, input)
                 }
                 CharacterTurnDecisionState::SpecialMove(move_state) => {
-                    move_state.update(ctx, &scene.allies, &scene.enemies)
+                    move_state.update(input, &scene.allies, &scene.enemies)
+                }
+                CharacterTurnDecisionState::ItemSelection(list_state) => {
+                    list_state.update(&scene.inventory, input)
+                }
+                CharacterTurnDecisionState::Item(item_state) => {
+                    item_state.update(input, &scene.allies, &scene.enemies)
                 }
             };
 
             match result {
                 Transition::None => (),
                 Transition::Skip(current_id) => {
-                    if current_id == scene.allies.len() - 1 {
-                        // TODO Whole turn system
-                        return Some(MacroBattleStates::TurnPreparation(TurnPreparationState {}));
-                    } else if scene.end_of_fight() {
+                    if scene.end_of_fight() {
                         // TODO Better way to handle end of battle
                         return Some(scene.get_end_state().unwrap());
                     } else {
@@ -132,22 +161,22 @@ impl BattleState for CharacterTurnDecisionState {
                         ));
                     }
                 }
+                Transition::Flee => return Some(scene.try_to_flee()),
                 Transition::SwitchTo(new_state) => {
                     return Some(MacroBattleStates::CharacterTurnDecision(new_state))
                 }
                 Transition::Validate(action) => {
                     let id = action.id_in_team;
+                    // Going back in the menu and choosing again replaces the previous choice.
+                    scene
+                        .allies_actions
+                        .retain(|record| record.id_in_team != id);
                     scene.allies_actions.push(action);
-                    if id == scene.allies.len() - 1 {
-                        // TODO Whole turn system
-                        return Some(MacroBattleStates::TurnPreparation(TurnPreparationState {}));
-                    } else {
-                        // TODO Whole turn system and action structure passing.
-                        return Some(CharacterTurnDecisionState::next_character(
-                            &scene.allies,
-                            id,
-                        ));
-                    }
+                    // TODO Whole turn system and action structure passing.
+                    return Some(CharacterTurnDecisionState::next_character(
+                        &scene.allies,
+                        id,
+                    ));
                 }
             }
         }
@@ -160,6 +189,7 @@ enum Transition {
     Skip(usize),                // last id
     Validate(AllyActionRecord), // TODO content
     SwitchTo(CharacterTurnDecisionState),
+    Flee,
 }
 
 #[derive(Copy, Clone)]
@@ -176,28 +206,33 @@ pub struct Menu {
 impl Menu {
     const MENU_NAMES: &'static [&'static str] = &["Bash", "SpecialMove", "Item", "Guard", "Flee"];
 
-    fn update(&mut self, characters: &[Actor], ctx: &Context) -> Transition {
+    fn update(
+        &mut self,
+        characters: &[Actor],
+        inventory: &Inventory,
+        input: &[UIAction],
+    ) -> Transition {
         if self.shared.ko_signal {
             return Transition::Skip(self.shared.current_character);
         }
-        if is_key_pressed(ctx, Key::Left) {
+        if input.contains(&UIAction::Left) {
             if self.shared.current_item > 0 {
                 self.shared.current_item -= 1;
             }
         }
-        if is_key_pressed(ctx, Key::Right) {
+        if input.contains(&UIAction::Right) {
             // TODO determine hud state from current character
             if self.shared.current_item < 4 {
                 self.shared.current_item += 1;
             }
         }
-        if is_key_pressed(ctx, Key::PageUp) {
+        if input.contains(&UIAction::PagePrev) {
             self.shared.current_item = 0;
         }
-        if is_key_pressed(ctx, Key::PageDown) {
+        if input.contains(&UIAction::PageNext) {
             self.shared.current_item = 4;
         }
-        if is_key_pressed(ctx, Key::Enter) {
+        if input.contains(&UIAction::Validate) {
             // TODO announce substate change
             if self.shared.current_item == 0 {
                 return Transition::SwitchTo(CharacterTurnDecisionState::Bash(
@@ -208,19 +243,25 @@ impl Menu {
                     },
                 ));
             }
-            if self.shared.current_item == 1 {
-                return Transition::SwitchTo(CharacterTurnDecisionState::SpecialMove(
-                    SpecialMoveTargetSelection {
+            if self.shared.current_item == 1
+                && !characters[self.shared.current_character].moves.is_empty()
+            {
+                return Transition::SwitchTo(CharacterTurnDecisionState::SpecialMoveSelection(
+                    SpecialMoveSelection {
                         shared: self.shared,
-                        // TODO Remember last selection and/or find first available target
-                        selected: Target::Single((Team::Enemy, 0)),
-                        // Uncomment this one instead if you want to test whole team targets.
-                        // selected: Target::WholeTeam(Team::Enemy),
-                        aim_ko_actors: false,
+                        cursor: ListCursor::default(),
                     },
                 ));
             }
-            if self.shared.current_item == 4 {
+            if self.shared.current_item == 2 && !inventory.is_empty() {
+                return Transition::SwitchTo(CharacterTurnDecisionState::ItemSelection(
+                    ItemSelection {
+                        shared: self.shared,
+                        cursor: ListCursor::default(),
+                    },
+                ));
+            }
+            if self.shared.current_item == 3 {
                 return Transition::Validate(AllyActionRecord {
                     id_in_team: self.shared.current_character,
                     registered_speed: characters[self.shared.current_character]
@@ -230,16 +271,18 @@ impl Menu {
                     action_type: ActionType::Guard,
                 });
             }
+            if self.shared.current_item == 4 {
+                return Transition::Flee;
+            }
         }
-        if is_key_pressed(ctx, Key::Backspace) && self.shared.current_character > 0 {
+        if input.contains(&UIAction::Cancel) && self.shared.current_character > 0 {
             let previous_characters = &characters[0..self.shared.current_character];
-            // TODO move that predicate somewhere else?
-            // TODO Also consider status effects later.
-            fn is_alive(tpl: &(usize, &Actor)) -> bool {
-                let &(_, actor) = tpl;
-                actor.hp.current_and_max().0 > 0
-            }
-            if let Some((i, _)) = previous_characters.iter().enumerate().rev().find(is_alive) {
+            if let Some((i, _)) = previous_characters
+                .iter()
+                .enumerate()
+                .rev()
+                .find(|(_, c)| can_choose_action(c))
+            {
                 return Transition::SwitchTo(CharacterTurnDecisionState::Menu(Menu {
                     shared: Breadcrumbs {
                         current_character: i,
@@ -281,8 +324,16 @@ trait TargetSelection {
         enemies
     }
     fn get_shared(&self) -> Breadcrumbs;
+    // What gets recorded once the target is validated.
+    fn action_type(&self) -> ActionType;
+    // Where backspace leads.
+    fn previous_state(&self) -> CharacterTurnDecisionState {
+        CharacterTurnDecisionState::Menu(Menu {
+            shared: self.get_shared(),
+        })
+    }
 
-    fn update(&mut self, ctx: &Context, allies: &[Actor], enemies: &[Actor]) -> Transition {
+    fn update(&mut self, input: &[UIAction], allies: &[Actor], enemies: &[Actor]) -> Transition {
         if self.get_shared().ko_signal {
             return Transition::Skip(self.get_shared().current_character);
         }
@@ -292,26 +343,24 @@ trait TargetSelection {
             Target::WholeTeam(_) => true,
         };
         if !target_whole_team {
-            if is_key_pressed(ctx, Key::Left) {
+            if input.contains(&UIAction::Left) {
                 self.cycle_selection_left(self.get_possible_targets(allies, enemies));
             }
-            if is_key_pressed(ctx, Key::Right) {
+            if input.contains(&UIAction::Right) {
                 // TODO determine hud state from current character
                 self.cycle_selection_right(self.get_possible_targets(allies, enemies));
             }
         }
 
-        if is_key_pressed(ctx, Key::Backspace) {
-            return Transition::SwitchTo(CharacterTurnDecisionState::Menu(Menu {
-                shared: self.get_shared(),
-            }));
+        if input.contains(&UIAction::Cancel) {
+            return Transition::SwitchTo(self.previous_state());
         }
 
-        if is_key_pressed(ctx, Key::Enter) {
+        if input.contains(&UIAction::Validate) {
             let current_character = self.get_shared().current_character;
             return Transition::Validate(AllyActionRecord {
                 id_in_team: current_character,
-                action_type: ActionType::Bash(self.current_target().clone()),
+                action_type: self.action_type(),
                 registered_speed: allies[current_character].stats.speed.multiplied(),
             });
         }
@@ -339,6 +388,10 @@ impl TargetSelection for BashTargetSelection {
     fn get_shared(&self) -> Breadcrumbs {
         self.shared
     }
+
+    fn action_type(&self) -> ActionType {
+        ActionType::Bash(self.selected.clone())
+    }
 }
 
 impl BashTargetSelection {
@@ -349,7 +402,13 @@ impl BashTargetSelection {
         };
         let enemy = &enemies[index];
         let mut debug_text = Text::new("--Bash selection--\n", assets.headupdaisy.clone());
-        debug_text.push_str(&format!("Char: {} ({})\n", enemy.name, index));
+        debug_text.push_str(&format!(
+            "Char: {} ({}){}{}\n",
+            enemy.name,
+            index,
+            status::describe(enemy),
+            element::describe_known(enemy)
+        ));
         debug_text.draw(
             ctx,
             DrawParams::new()
@@ -359,11 +418,157 @@ impl BashTargetSelection {
     }
 }
 
+// Cursor over a paginated list (special moves, items).
+#[derive(Copy, Clone, Default)]
+struct ListCursor {
+    index: usize,
+}
+
+impl ListCursor {
+    const PAGE_SIZE: usize = 4;
+
+    fn page_start(&self) -> usize {
+        self.index - self.index % ListCursor::PAGE_SIZE
+    }
+
+    fn update(&mut self, input: &[UIAction], len: usize) {
+        let page_start = self.page_start();
+        if input.contains(&UIAction::Up) && self.index > page_start {
+            self.index -= 1;
+        }
+        if input.contains(&UIAction::Down)
+            && self.index + 1 < len
+            && self.index + 1 < page_start + ListCursor::PAGE_SIZE
+        {
+            self.index += 1;
+        }
+        if input.contains(&UIAction::PagePrev) && page_start > 0 {
+            self.index -= ListCursor::PAGE_SIZE;
+        }
+        if input.contains(&UIAction::PageNext) && page_start + ListCursor::PAGE_SIZE < len {
+            self.index = (self.index + ListCursor::PAGE_SIZE).min(len - 1);
+        }
+    }
+
+    // Draws the current page. Entries are (label, enabled), disabled ones are greyed out.
+    fn draw(&self, ctx: &mut Context, assets: &Assets, title: &str, entries: &[(String, bool)]) {
+        let num_pages = entries.len().div_ceil(ListCursor::PAGE_SIZE);
+        let mut header = Text::new(
+            format!(
+                "--{} ({}/{})--\n",
+                title,
+                self.index / ListCursor::PAGE_SIZE + 1,
+                num_pages
+            ),
+            assets.headupdaisy.clone(),
+        );
+        header.draw(
+            ctx,
+            DrawParams::new()
+                .color(Color::rgb8(0xeb, 0xdb, 0xb2))
+                .position(Vec2::new(16., 360.)),
+        );
+
+        for (line, (i, (label, enabled))) in entries
+            .iter()
+            .enumerate()
+            .skip(self.page_start())
+            .take(ListCursor::PAGE_SIZE)
+            .enumerate()
+        {
+            let cursor = if i == self.index { ">" } else { " " };
+            let mut entry_text =
+                Text::new(format!("{} {}", cursor, label), assets.headupdaisy.clone());
+            let color = if *enabled {
+                Color::rgb8(0xeb, 0xdb, 0xb2)
+            } else {
+                Color::rgb8(0x92, 0x83, 0x74)
+            };
+            entry_text.draw(
+                ctx,
+                DrawParams::new()
+                    .color(color)
+                    .position(Vec2::new(16., 384. + 24. * line as f32)),
+            );
+        }
+    }
+}
+
+// List of the current character's moves.
+pub struct SpecialMoveSelection {
+    shared: Breadcrumbs,
+    cursor: ListCursor,
+}
+
+impl SpecialMoveSelection {
+    fn can_afford(character: &Actor, move_id: MoveId) -> bool {
+        character.pp.target() >= special_move::get(move_id).pp_cost
+    }
+
+    fn update(&mut self, characters: &[Actor], input: &[UIAction]) -> Transition {
+        if self.shared.ko_signal {
+            return Transition::Skip(self.shared.current_character);
+        }
+        let character = &characters[self.shared.current_character];
+        self.cursor.update(input, character.moves.len());
+
+        if input.contains(&UIAction::Cancel) {
+            return Transition::SwitchTo(CharacterTurnDecisionState::Menu(Menu {
+                shared: self.shared,
+            }));
+        }
+
+        if input.contains(&UIAction::Validate) {
+            let move_id = character.moves[self.cursor.index];
+            // Greyed out moves can't be picked.
+            if !SpecialMoveSelection::can_afford(character, move_id) {
+                return Transition::None;
+            }
+            let caster = (Team::Ally, self.shared.current_character);
+            let special_move = special_move::get(move_id);
+            return Transition::SwitchTo(CharacterTurnDecisionState::SpecialMove(
+                SpecialMoveTargetSelection {
+                    shared: self.shared,
+                    // TODO Remember last selection and/or find first available target
+                    selected: special_move.target.default_target(&caster),
+                    move_id,
+                    aim_ko_actors: special_move.effect.aims_ko_actors(),
+                    list_cursor: self.cursor,
+                },
+            ));
+        }
+
+        Transition::None
+    }
+
+    fn draw(&self, ctx: &mut Context, assets: &Assets, character: &Actor) {
+        let entries: Vec<(String, bool)> = character
+            .moves
+            .iter()
+            .map(|&move_id| {
+                let special_move = special_move::get(move_id);
+                (
+                    format!(
+                        "{:16} {:3} PP",
+                        special_move.display_name(),
+                        special_move.pp_cost
+                    ),
+                    SpecialMoveSelection::can_afford(character, move_id),
+                )
+            })
+            .collect();
+        self.cursor.draw(ctx, assets, "Special moves", &entries);
+    }
+}
+
 pub struct SpecialMoveTargetSelection {
     shared: Breadcrumbs,
     selected: Target,
     // SpecialMove-dependant values
+    move_id: MoveId,
     aim_ko_actors: bool,
+    // To get back at the same place in the move list
+    list_cursor: ListCursor,
 }
 
 impl TargetSelection for SpecialMoveTargetSelection {
@@ -372,28 +577,60 @@ impl TargetSelection for SpecialMoveTargetSelection {
     }
 
     fn cycle_selection_left(&mut self, possible_targets: &[Actor]) {
+        if !special_move::get(self.move_id).target.is_selectable() {
+            return;
+        }
         self.selected =
             cycle_previous_target(self.selected.clone(), self.aim_ko_actors, possible_targets)
     }
     fn cycle_selection_right(&mut self, possible_targets: &[Actor]) {
+        if !special_move::get(self.move_id).target.is_selectable() {
+            return;
+        }
         self.selected =
             cycle_next_target(self.selected.clone(), self.aim_ko_actors, possible_targets)
     }
 
+    fn get_possible_targets<'a>(&self, allies: &'a [Actor], enemies: &'a [Actor]) -> &'a [Actor] {
+        get_possible_targets(&self.selected, allies, enemies)
+    }
+
     fn get_shared(&self) -> Breadcrumbs {
         self.shared
     }
+
+    fn action_type(&self) -> ActionType {
+        ActionType::SpecialMove(self.move_id, self.selected.clone())
+    }
+
+    fn previous_state(&self) -> CharacterTurnDecisionState {
+        CharacterTurnDecisionState::SpecialMoveSelection(SpecialMoveSelection {
+            shared: self.shared,
+            cursor: self.list_cursor,
+        })
+    }
 }
 
 impl SpecialMoveTargetSelection {
-    // TODO pass both enemies and allies
-    fn draw(&self, ctx: &mut Context, assets: &Assets, enemies: &[Actor]) {
+    fn draw(&self, ctx: &mut Context, assets: &Assets, possible_targets: &[Actor]) {
         let mut debug_text = Text::new("--SpecialMove selection--\n", assets.headupdaisy.clone());
-        if let Target::Single((_, id)) = &self.selected {
-            let enemy = &enemies[*id];
-            debug_text.push_str(&format!("Char: {} ({})\n", enemy.name, *id));
-        } else {
-            debug_text.push_str("All enemies.");
+        debug_text.push_str(&format!(
+            "Move: {}\n",
+            special_move::get(self.move_id).display_name()
+        ));
+        match &self.selected {
+            Target::Single((_, id)) => {
+                let target = &possible_targets[*id];
+                debug_text.push_str(&format!(
+                    "Char: {} ({}){}{}\n",
+                    target.name,
+                    *id,
+                    status::describe(target),
+                    element::describe_known(target)
+                ));
+            }
+            Target::WholeTeam(Team::Enemy) => debug_text.push_str("All enemies."),
+            Target::WholeTeam(Team::Ally) => debug_text.push_str("All allies."),
         }
         debug_text.draw(
             ctx,
@@ -404,6 +641,138 @@ impl SpecialMoveTargetSelection {
     }
 }
 
+// List of the party's items.
+pub struct ItemSelection {
+    shared: Breadcrumbs,
+    cursor: ListCursor,
+}
+
+impl ItemSelection {
+    fn update(&mut self, inventory: &Inventory, input: &[UIAction]) -> Transition {
+        if self.shared.ko_signal {
+            return Transition::Skip(self.shared.current_character);
+        }
+        let stacks = inventory.stacks();
+        // The inventory can shrink during a turn, don't point past its end.
+        if self.cursor.index >= stacks.len() {
+            self.cursor = ListCursor::default();
+        }
+        self.cursor.update(input, stacks.len());
+
+        if input.contains(&UIAction::Cancel) || stacks.is_empty() {
+            return Transition::SwitchTo(CharacterTurnDecisionState::Menu(Menu {
+                shared: self.shared,
+            }));
+        }
+
+        if input.contains(&UIAction::Validate) {
+            let (item_id, _) = stacks[self.cursor.index];
+            let caster = (Team::Ally, self.shared.current_character);
+            let item = item::get(item_id);
+            return Transition::SwitchTo(CharacterTurnDecisionState::Item(ItemTargetSelection {
+                shared: self.shared,
+                // TODO Remember last selection and/or find first available target
+                selected: item.target.default_target(&caster),
+                item_id,
+                aim_ko_actors: item.effect.aims_ko_actors(),
+                list_cursor: self.cursor,
+            }));
+        }
+
+        Transition::None
+    }
+
+    fn draw(&self, ctx: &mut Context, assets: &Assets, inventory: &Inventory) {
+        let entries: Vec<(String, bool)> = inventory
+            .stacks()
+            .iter()
+            .map(|&(item_id, count)| (format!("{:16} x{:2}", item::get(item_id).name, count), true))
+            .collect();
+        self.cursor.draw(ctx, assets, "Items", &entries);
+    }
+}
+
+pub struct ItemTargetSelection {
+    shared: Breadcrumbs,
+    selected: Target,
+    item_id: ItemId,
+    aim_ko_actors: bool,
+    list_cursor: ListCursor,
+}
+
+impl TargetSelection for ItemTargetSelection {
+    fn current_target(&self) -> &Target {
+        &self.selected
+    }
+
+    fn cycle_selection_left(&mut self, possible_targets: &[Actor]) {
+        if !item::get(self.item_id).target.is_selectable() {
+            return;
+        }
+        self.selected =
+            cycle_previous_target(self.selected.clone(), self.aim_ko_actors, possible_targets)
+    }
+    fn cycle_selection_right(&mut self, possible_targets: &[Actor]) {
+        if !item::get(self.item_id).target.is_selectable() {
+            return;
+        }
+        self.selected =
+            cycle_next_target(self.selected.clone(), self.aim_ko_actors, possible_targets)
+    }
+
+    fn get_possible_targets<'a>(&self, allies: &'a [Actor], enemies: &'a [Actor]) -> &'a [Actor] {
+        get_possible_targets(&self.selected, allies, enemies)
+    }
+
+    fn get_shared(&self) -> Breadcrumbs {
+        self.shared
+    }
+
+    fn action_type(&self) -> ActionType {
+        ActionType::Item(self.item_id, self.selected.clone())
+    }
+
+    fn previous_state(&self) -> CharacterTurnDecisionState {
+        CharacterTurnDecisionState::ItemSelection(ItemSelection {
+            shared: self.shared,
+            cursor: self.list_cursor,
+        })
+    }
+}
+
+impl ItemTargetSelection {
+    fn draw(&self, ctx: &mut Context, assets: &Assets, possible_targets: &[Actor]) {
+        let mut debug_text = Text::new("--Item selection--\n", assets.headupdaisy.clone());
+        debug_text.push_str(&format!("Item: {}\n", item::get(self.item_id).name));
+        match &self.selected {
+            Target::Single((_, id)) => {
+                let target = &possible_targets[*id];
+                debug_text.push_str(&format!(
+                    "Char: {} ({}){}{}\n",
+                    target.name,
+                    *id,
+                    status::describe(target),
+                    element::describe_known(target)
+                ));
+            }
+            Target::WholeTeam(Team::Enemy) => debug_text.push_str("All enemies."),
+            Target::WholeTeam(Team::Ally) => debug_text.push_str("All allies."),
+        }
+        debug_text.draw(
+            ctx,
+            DrawParams::new()
+                .color(Color::rgb8(0xeb, 0xdb, 0xb2))
+                .position(Vec2::new(16., 360.)),
+        );
+    }
+}
+
+// Generic helper
+// Whether a character gets to pick an action this turn.
+fn can_choose_action(character: &Actor) -> bool {
+    character.hp.current_and_max().0 > 0 && status::can_choose_action(character)
+}
+
 // Generic helper
 fn cycle_previous_target(
     current: Target,
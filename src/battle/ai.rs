@@ -0,0 +1,211 @@
+use crate::battle::effect::Effect;
+use crate::battle::element::{Affinity, Element};
+use crate::battle::rng::BattleRng;
+use crate::battle::special_move::{self, MoveId, TargetRule};
+use crate::battle::{ActionType, Actor, Target, Team};
+
+// What a brain gets to look at. Read-only, brains can't cheat by poking at the actors.
+pub struct BattleView<'a> {
+    pub allies: &'a [Actor],
+    pub enemies: &'a [Actor],
+    pub turn: u32,
+    // Index of the deciding enemy in `enemies`.
+    pub me: usize,
+}
+
+impl<'a> BattleView<'a> {
+    pub fn myself(&self) -> &Actor {
+        &self.enemies[self.me]
+    }
+}
+
+pub trait EnemyBrain {
+    // Short description for the battle log.
+    fn name(&self) -> &str;
+    fn decide(&mut self, view: &BattleView, rng: &mut BattleRng) -> ActionType;
+}
+
+// Generic helpers
+
+fn living_indices(actors: &[Actor]) -> Vec<usize> {
+    actors
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| a.hp.current_and_max().0 > 0)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn hp_ratio(actor: &Actor) -> f32 {
+    let (hp, max_hp) = actor.hp.current_and_max();
+    if max_hp == 0 {
+        return 0.;
+    }
+    f32::from(hp) / f32::from(max_hp)
+}
+
+fn random_living_target(team: Team, actors: &[Actor], rng: &mut BattleRng) -> Target {
+    let living = living_indices(actors);
+    if living.is_empty() {
+        return Target::Single((team, 0));
+    }
+    Target::Single((team, living[rng.gen_range(0..living.len())]))
+}
+
+// From an enemy's point of view, "enemies" are the party.
+fn target_for_rule(rule: TargetRule, view: &BattleView, rng: &mut BattleRng) -> Target {
+    match rule {
+        TargetRule::SingleEnemy => random_living_target(Team::Ally, view.allies, rng),
+        TargetRule::SingleAlly => random_living_target(Team::Enemy, view.enemies, rng),
+        _ => rule.default_target(&(Team::Enemy, view.me)),
+    }
+}
+
+fn can_afford(actor: &Actor, move_id: MoveId) -> bool {
+    actor.pp.target() >= special_move::get(move_id).pp_cost
+}
+
+// Built-in brains
+
+// Bashes whoever, as long as they're standing.
+pub struct RandomTarget;
+
+impl EnemyBrain for RandomTarget {
+    fn name(&self) -> &str {
+        "random target"
+    }
+
+    fn decide(&mut self, view: &BattleView, rng: &mut BattleRng) -> ActionType {
+        ActionType::Bash(random_living_target(Team::Ally, view.allies, rng))
+    }
+}
+
+// Goes for the kill.
+pub struct LowestHp;
+
+impl EnemyBrain for LowestHp {
+    fn name(&self) -> &str {
+        "lowest HP"
+    }
+
+    fn decide(&mut self, view: &BattleView, rng: &mut BattleRng) -> ActionType {
+        match living_indices(view.allies)
+            .into_iter()
+            .min_by_key(|&i| view.allies[i].hp.current_and_max().0)
+        {
+            Some(i) => ActionType::Bash(Target::Single((Team::Ally, i))),
+            None => ActionType::Bash(random_living_target(Team::Ally, view.allies, rng)),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum MoveChoice {
+    Bash,
+    Guard,
+    SpecialMove(MoveId),
+}
+
+// Picks a move with a weighted roll. Moves the enemy can't afford fall back to bashing.
+pub struct WeightedMoveTable {
+    pub entries: Vec<(u32, MoveChoice)>,
+}
+
+impl EnemyBrain for WeightedMoveTable {
+    fn name(&self) -> &str {
+        "move table"
+    }
+
+    fn decide(&mut self, view: &BattleView, rng: &mut BattleRng) -> ActionType {
+        let total: u32 = self.entries.iter().map(|(weight, _)| weight).sum();
+        let mut choice = MoveChoice::Bash;
+        if total > 0 {
+            let mut roll = rng.gen_range(0..total);
+            for &(weight, entry) in self.entries.iter() {
+                if roll < weight {
+                    choice = entry;
+                    break;
+                }
+                roll -= weight;
+            }
+        }
+
+        match choice {
+            MoveChoice::SpecialMove(move_id) if can_afford(view.myself(), move_id) => {
+                let target = target_for_rule(special_move::get(move_id).target, view, rng);
+                ActionType::SpecialMove(move_id, target)
+            }
+            MoveChoice::Guard => ActionType::Guard,
+            _ => ActionType::Bash(random_living_target(Team::Ally, view.allies, rng)),
+        }
+    }
+}
+
+// Heals the most hurt fellow enemy once they go below the threshold, otherwise defers to
+// another brain.
+pub struct Healer {
+    pub threshold: f32,
+    pub heal_move: MoveId,
+    pub fallback: Box<dyn EnemyBrain>,
+}
+
+impl EnemyBrain for Healer {
+    fn name(&self) -> &str {
+        "healer"
+    }
+
+    fn decide(&mut self, view: &BattleView, rng: &mut BattleRng) -> ActionType {
+        if can_afford(view.myself(), self.heal_move) {
+            let most_hurt = living_indices(view.enemies)
+                .into_iter()
+                .filter(|&i| hp_ratio(&view.enemies[i]) < self.threshold)
+                .min_by(|&a, &b| {
+                    hp_ratio(&view.enemies[a])
+                        .partial_cmp(&hp_ratio(&view.enemies[b]))
+                        .unwrap()
+                });
+            if let Some(i) = most_hurt {
+                let target = match special_move::get(self.heal_move).target {
+                    TargetRule::SingleAlly => Target::Single((Team::Enemy, i)),
+                    rule => target_for_rule(rule, view, rng),
+                };
+                return ActionType::SpecialMove(self.heal_move, target);
+            }
+        }
+        self.fallback.decide(view, rng)
+    }
+}
+
+// Hits a party member with an element they are weak to, if it has an affordable move for that,
+// otherwise defers to another brain.
+pub struct ExploitWeakness {
+    pub fallback: Box<dyn EnemyBrain>,
+}
+
+impl EnemyBrain for ExploitWeakness {
+    fn name(&self) -> &str {
+        "exploit weakness"
+    }
+
+    fn decide(&mut self, view: &BattleView, rng: &mut BattleRng) -> ActionType {
+        let me = view.myself();
+        for &move_id in me.moves.iter() {
+            let special_move = special_move::get(move_id);
+            let damaging = matches!(special_move.effect, Effect::Damage(_));
+            if !damaging || special_move.element == Element::Neutral || !can_afford(me, move_id) {
+                continue;
+            }
+            let weak = living_indices(view.allies)
+                .into_iter()
+                .find(|&i| view.allies[i].affinities.get(special_move.element) == Affinity::Weak);
+            if let Some(i) = weak {
+                let target = match special_move.target {
+                    TargetRule::SingleEnemy => Target::Single((Team::Ally, i)),
+                    rule => target_for_rule(rule, view, rng),
+                };
+                return ActionType::SpecialMove(move_id, target);
+            }
+        }
+        self.fallback.decide(view, rng)
+    }
+}
@@ -0,0 +1,71 @@
+use crate::battle::turn::TurnUnrollState;
+use crate::battle::{ActorIdentifier, MacroBattleStates, UIAction};
+use crate::battle::{BattleState, BattleStateTransition, CharacterKoSignal};
+use crate::timer::Timer;
+use crate::{Assets, BattleScene};
+use tetra::graphics::text::Text;
+use tetra::graphics::{Color, DrawParams};
+use tetra::math::Vec2;
+use tetra::Context;
+
+// The "CharacterKo" interruption from the formalization graph: the turn is paused for a moment
+// when a character falls, then goes on where it was, or ends on a game over.
+pub struct CharacterFallsState {
+    fallen: Vec<usize>,
+    time: Timer,
+    // Only taken when resuming.
+    interrupted: Option<TurnUnrollState>,
+}
+
+impl CharacterFallsState {
+    pub fn new(fallen: Vec<usize>, interrupted: TurnUnrollState) -> CharacterFallsState {
+        CharacterFallsState {
+            fallen,
+            time: Timer::new(1.0),
+            interrupted: Some(interrupted),
+        }
+    }
+
+    pub fn draw(scene: &BattleScene, ctx: &mut Context, assets: &Assets) {
+        let mut debug_text = Text::new("--K.O.--\n", assets.headupdaisy.clone());
+        if let MacroBattleStates::CharacterFalls(state) = &scene.state {
+            for id in state.fallen.iter() {
+                debug_text.push_str(&format!("{} fell!\n", scene.allies[*id].name));
+            }
+        }
+        debug_text.draw(
+            ctx,
+            DrawParams::new()
+                .color(Color::rgb8(0xeb, 0xdb, 0xb2))
+                .position(Vec2::new(16., 360.)),
+        );
+    }
+}
+
+// Somebody else falling during the interruption just joins the pile.
+impl CharacterKoSignal for CharacterFallsState {
+    fn on_character_ko(&mut self, id: ActorIdentifier) {
+        if let Some(unroll) = &mut self.interrupted {
+            unroll.on_character_ko(id.clone());
+        }
+        self.fallen.push(id.1);
+    }
+}
+
+impl BattleState for CharacterFallsState {
+    fn update(scene: &mut BattleScene, dt: f32, _input: &[UIAction]) -> BattleStateTransition {
+        let end_state = scene.get_end_state();
+        if let MacroBattleStates::CharacterFalls(state) = &mut scene.state {
+            state.time.tick(dt);
+            if !state.time.done() {
+                return None;
+            }
+            if end_state.is_some() {
+                return end_state;
+            }
+            // Resume the interrupted state.
+            return state.interrupted.take().map(MacroBattleStates::TurnUnroll);
+        }
+        None
+    }
+}
@@ -0,0 +1,529 @@
+use crate::battle::ai::{
+    EnemyBrain, ExploitWeakness, Healer, LowestHp, MoveChoice, RandomTarget, WeightedMoveTable,
+};
+use crate::battle::element::{Affinities, Affinity, Element};
+use crate::battle::growth::{Growth, Progression, MAX_LEVEL};
+use crate::battle::item::{self, Inventory};
+use crate::battle::rhythm::{RhythmConfig, TrackInfo};
+use crate::battle::script::ScriptedBrain;
+use crate::battle::special_move::{self, MoveId};
+use crate::battle::stat::{Stat, StatBlock};
+use crate::battle::victory::Rewards;
+use crate::battle::{Actor, BattleScene, Opening};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+// Party, enemies and encounters described in RON files under res/data.
+// Everything is checked once at load time so a typo shows up before the battle starts rather
+// than in the middle of a turn.
+
+pub const DATA_DIR: &str = "res/data";
+
+// Sprites the game knows how to draw. Keep in sync with `Assets`.
+const KNOWN_SPRITES: &[&str] = &["robot", "blowharder"];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CharacterDef {
+    pub name: String,
+    // At level 1.
+    pub stats: StatBlock,
+    #[serde(default = "first_level")]
+    pub level: u16,
+    #[serde(default)]
+    pub growth: Growth,
+    #[serde(default)]
+    pub equipment: StatBlock,
+    // Known from the start, whatever the level.
+    #[serde(default)]
+    pub moves: Vec<String>,
+    // Level and move learned at that level.
+    #[serde(default)]
+    pub learnset: Vec<(u16, String)>,
+    #[serde(default)]
+    pub affinities: Vec<(Element, Affinity)>,
+}
+
+fn first_level() -> u16 {
+    1
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PartyDef {
+    pub members: Vec<CharacterDef>,
+    #[serde(default)]
+    pub inventory: Vec<(String, u16)>,
+}
+
+#[derive(Deserialize)]
+pub enum MoveChoiceDef {
+    Bash,
+    Guard,
+    SpecialMove(String),
+}
+
+#[derive(Deserialize, Default)]
+pub enum BrainDef {
+    #[default]
+    RandomTarget,
+    LowestHp,
+    MoveTable(Vec<(u32, MoveChoiceDef)>),
+    Healer {
+        threshold: f32,
+        heal_move: String,
+        fallback: Box<BrainDef>,
+    },
+    Script {
+        path: String,
+        fallback: Box<BrainDef>,
+    },
+    ExploitWeakness {
+        fallback: Box<BrainDef>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyDef {
+    pub name: String,
+    pub sprite: String,
+    pub stats: StatBlock,
+    #[serde(default)]
+    pub moves: Vec<String>,
+    #[serde(default)]
+    pub brain: BrainDef,
+    #[serde(default)]
+    pub affinities: Vec<(Element, Affinity)>,
+    #[serde(default)]
+    pub exp: u32,
+    #[serde(default)]
+    pub money: u32,
+    // Item name and chance to drop it.
+    #[serde(default)]
+    pub drops: Vec<(String, f32)>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EncounterDef {
+    // Keys into the enemy definitions, an enemy can appear several times.
+    pub enemies: Vec<String>,
+    #[serde(default)]
+    pub opening: Opening,
+    // Key into the tracks, a plain 120 BPM otherwise.
+    #[serde(default)]
+    pub music: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MusicDef {
+    #[serde(default)]
+    pub timing: RhythmConfig,
+    pub tracks: BTreeMap<String, TrackInfo>,
+}
+
+pub enum DataError {
+    Io(String, std::io::Error),
+    Parse(String, ron::error::SpannedError),
+    UnknownMove {
+        owner: String,
+        name: String,
+    },
+    UnknownSprite {
+        owner: String,
+        name: String,
+    },
+    UnknownItem {
+        owner: String,
+        name: String,
+    },
+    UnknownEnemy {
+        encounter: String,
+        name: String,
+    },
+    UnknownEncounter {
+        name: String,
+        available: Vec<String>,
+    },
+    InvalidValue {
+        owner: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataError::Io(path, err) => write!(f, "[ERROR] Couldn't read {}: {}", path, err),
+            DataError::Parse(path, err) => write!(f, "[ERROR] {}:{}", path, err),
+            DataError::UnknownMove { owner, name } => {
+                write!(f, "[ERROR] {} knows an unknown move: \"{}\"", owner, name)
+            }
+            DataError::UnknownSprite { owner, name } => write!(
+                f,
+                "[ERROR] {} uses an unknown sprite: \"{}\" (known sprites: {})",
+                owner,
+                name,
+                KNOWN_SPRITES.join(", ")
+            ),
+            DataError::UnknownItem { owner, name } => {
+                write!(
+                    f,
+                    "[ERROR] {} refers to an unknown item: \"{}\"",
+                    owner, name
+                )
+            }
+            DataError::UnknownEnemy { encounter, name } => write!(
+                f,
+                "[ERROR] Encounter \"{}\" refers to an unknown enemy: \"{}\"",
+                encounter, name
+            ),
+            DataError::UnknownEncounter { name, available } => write!(
+                f,
+                "[ERROR] No encounter named \"{}\" (available: {})",
+                name,
+                available.join(", ")
+            ),
+            DataError::InvalidValue { owner, reason } => {
+                write!(f, "[ERROR] {}: {}", owner, reason)
+            }
+        }
+    }
+}
+
+pub struct GameData {
+    pub party: PartyDef,
+    pub enemies: BTreeMap<String, EnemyDef>,
+    pub encounters: BTreeMap<String, EncounterDef>,
+    pub music: MusicDef,
+}
+
+fn read_ron<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, DataError> {
+    let display = path.display().to_string();
+    let source =
+        std::fs::read_to_string(path).map_err(|err| DataError::Io(display.clone(), err))?;
+    ron::from_str(&source).map_err(|err| DataError::Parse(display, err))
+}
+
+fn resolve_move(owner: &str, name: &str) -> Result<MoveId, DataError> {
+    special_move::find_by_name(name).ok_or_else(|| DataError::UnknownMove {
+        owner: String::from(owner),
+        name: String::from(name),
+    })
+}
+
+fn resolve_moves(owner: &str, names: &[String]) -> Result<Vec<MoveId>, DataError> {
+    names.iter().map(|name| resolve_move(owner, name)).collect()
+}
+
+fn resolve_learnset(
+    owner: &str,
+    learnset: &[(u16, String)],
+) -> Result<Vec<(u16, MoveId)>, DataError> {
+    learnset
+        .iter()
+        .map(|(level, name)| Ok((*level, resolve_move(owner, name)?)))
+        .collect()
+}
+
+fn invalid(owner: &str, reason: &str) -> DataError {
+    DataError::InvalidValue {
+        owner: String::from(owner),
+        reason: String::from(reason),
+    }
+}
+
+fn check_stats(owner: &str, stats: &StatBlock) -> Result<(), DataError> {
+    if stats.hp == 0 {
+        return Err(invalid(owner, "max HP must be above 0"));
+    }
+    Ok(())
+}
+
+fn check_affinities(owner: &str, affinities: &[(Element, Affinity)]) -> Result<(), DataError> {
+    for (i, (element, _)) in affinities.iter().enumerate() {
+        if *element == Element::Neutral {
+            return Err(invalid(owner, "Neutral can't have an affinity"));
+        }
+        if affinities[..i].iter().any(|(e, _)| e == element) {
+            return Err(invalid(
+                owner,
+                &format!("{} is listed twice in the affinities", element.name()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_brain(owner: &str, brain: &BrainDef) -> Result<(), DataError> {
+    match brain {
+        BrainDef::RandomTarget | BrainDef::LowestHp => Ok(()),
+        BrainDef::MoveTable(entries) => {
+            if entries.iter().all(|(weight, _)| *weight == 0) {
+                return Err(invalid(
+                    owner,
+                    "the move table needs at least one weighted entry",
+                ));
+            }
+            for (_, choice) in entries {
+                if let MoveChoiceDef::SpecialMove(name) = choice {
+                    resolve_move(owner, name)?;
+                }
+            }
+            Ok(())
+        }
+        BrainDef::Healer {
+            threshold,
+            heal_move,
+            fallback,
+        } => {
+            if !(0. ..=1.).contains(threshold) {
+                return Err(invalid(
+                    owner,
+                    "the healer threshold must be between 0 and 1",
+                ));
+            }
+            resolve_move(owner, heal_move)?;
+            check_brain(owner, fallback)
+        }
+        BrainDef::Script { path, fallback } => {
+            if !Path::new(path).is_file() {
+                return Err(invalid(owner, &format!("no script at {}", path)));
+            }
+            check_brain(owner, fallback)
+        }
+        BrainDef::ExploitWeakness { fallback } => check_brain(owner, fallback),
+    }
+}
+
+// Only called on validated definitions.
+fn build_brain(brain: &BrainDef) -> Box<dyn EnemyBrain> {
+    match brain {
+        BrainDef::RandomTarget => Box::new(RandomTarget),
+        BrainDef::LowestHp => Box::new(LowestHp),
+        BrainDef::MoveTable(entries) => Box::new(WeightedMoveTable {
+            entries: entries
+                .iter()
+                .map(|(weight, choice)| {
+                    let choice = match choice {
+                        MoveChoiceDef::Bash => MoveChoice::Bash,
+                        MoveChoiceDef::Guard => MoveChoice::Guard,
+                        MoveChoiceDef::SpecialMove(name) => {
+                            MoveChoice::SpecialMove(special_move::find_by_name(name).unwrap())
+                        }
+                    };
+                    (*weight, choice)
+                })
+                .collect(),
+        }),
+        BrainDef::Healer {
+            threshold,
+            heal_move,
+            fallback,
+        } => Box::new(Healer {
+            threshold: *threshold,
+            heal_move: special_move::find_by_name(heal_move).unwrap(),
+            fallback: build_brain(fallback),
+        }),
+        BrainDef::Script { path, fallback } => {
+            Box::new(ScriptedBrain::load(path, build_brain(fallback)))
+        }
+        BrainDef::ExploitWeakness { fallback } => Box::new(ExploitWeakness {
+            fallback: build_brain(fallback),
+        }),
+    }
+}
+
+impl GameData {
+    pub fn load(dir: &str) -> Result<GameData, DataError> {
+        let dir = Path::new(dir);
+        let data = GameData {
+            party: read_ron(&dir.join("party.ron"))?,
+            enemies: read_ron(&dir.join("enemies.ron"))?,
+            encounters: read_ron(&dir.join("encounters.ron"))?,
+            music: read_ron(&dir.join("music.ron"))?,
+        };
+        data.validate()?;
+        Ok(data)
+    }
+
+    fn validate(&self) -> Result<(), DataError> {
+        if self.party.members.is_empty() {
+            return Err(invalid("Party", "there must be at least one member"));
+        }
+        for member in self.party.members.iter() {
+            check_stats(&member.name, &member.stats)?;
+            if member.level == 0 || member.level > MAX_LEVEL {
+                return Err(invalid(
+                    &member.name,
+                    &format!("the level must be between 1 and {}", MAX_LEVEL),
+                ));
+            }
+            let g = &member.growth;
+            if [g.hp, g.pp, g.offense, g.defense, g.speed, g.iq]
+                .iter()
+                .any(|rate| rate.is_nan() || *rate < 0.)
+            {
+                return Err(invalid(&member.name, "growth rates can't be negative"));
+            }
+            resolve_moves(&member.name, &member.moves)?;
+            resolve_learnset(&member.name, &member.learnset)?;
+            check_affinities(&member.name, &member.affinities)?;
+        }
+        for (name, _) in self.party.inventory.iter() {
+            if item::find_by_name(name).is_none() {
+                return Err(DataError::UnknownItem {
+                    owner: String::from("The party"),
+                    name: name.clone(),
+                });
+            }
+        }
+
+        for (key, enemy) in self.enemies.iter() {
+            let owner = format!("Enemy \"{}\"", key);
+            if !KNOWN_SPRITES.contains(&enemy.sprite.as_str()) {
+                return Err(DataError::UnknownSprite {
+                    owner,
+                    name: enemy.sprite.clone(),
+                });
+            }
+            check_stats(&owner, &enemy.stats)?;
+            resolve_moves(&owner, &enemy.moves)?;
+            check_brain(&owner, &enemy.brain)?;
+            check_affinities(&owner, &enemy.affinities)?;
+            for (name, chance) in enemy.drops.iter() {
+                if item::find_by_name(name).is_none() {
+                    return Err(DataError::UnknownItem {
+                        owner,
+                        name: name.clone(),
+                    });
+                }
+                if !(0. ..=1.).contains(chance) {
+                    return Err(invalid(&owner, "drop chances must be between 0 and 1"));
+                }
+            }
+        }
+
+        let timing = &self.music.timing;
+        if timing.window.is_nan() || timing.window <= 0. || !timing.latency.is_finite() {
+            return Err(invalid(
+                "Music timing",
+                "the window must be above 0 and the latency a number",
+            ));
+        }
+        for (key, track) in self.music.tracks.iter() {
+            if track.bpm.is_nan() || track.bpm <= 0. || !track.offset.is_finite() {
+                return Err(invalid(
+                    &format!("Track \"{}\"", key),
+                    "the BPM must be above 0 and the offset a number",
+                ));
+            }
+        }
+
+        for (key, encounter) in self.encounters.iter() {
+            if encounter.enemies.is_empty() {
+                return Err(invalid(
+                    &format!("Encounter \"{}\"", key),
+                    "there must be at least one enemy",
+                ));
+            }
+            if let Some(track) = &encounter.music {
+                if !self.music.tracks.contains_key(track) {
+                    return Err(invalid(
+                        &format!("Encounter \"{}\"", key),
+                        &format!("no track named \"{}\"", track),
+                    ));
+                }
+            }
+            for name in encounter.enemies.iter() {
+                if !self.enemies.contains_key(name) {
+                    return Err(DataError::UnknownEnemy {
+                        encounter: key.clone(),
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn start_encounter(&self, name: &str, seed: u64) -> Result<BattleScene, DataError> {
+        let encounter = match self.encounters.get(name) {
+            Some(encounter) => encounter,
+            None => {
+                return Err(DataError::UnknownEncounter {
+                    name: String::from(name),
+                    available: self.encounters.keys().cloned().collect(),
+                })
+            }
+        };
+
+        let allies = self
+            .party
+            .members
+            .iter()
+            .map(|member| {
+                let progression = Progression::new(
+                    member.level,
+                    member.stats,
+                    member.growth,
+                    member.equipment,
+                    resolve_learnset(&member.name, &member.learnset)?,
+                );
+                let mut actor = Actor::character(&member.name, progression);
+                actor.affinities = Affinities::new(member.affinities.clone());
+                for id in resolve_moves(&member.name, &member.moves)? {
+                    if !actor.moves.contains(&id) {
+                        actor.moves.push(id);
+                    }
+                }
+                Ok(actor)
+            })
+            .collect::<Result<Vec<Actor>, DataError>>()?;
+
+        let mut inventory = Inventory::default();
+        for (name, count) in self.party.inventory.iter() {
+            inventory.add(item::find_by_name(name).unwrap(), *count);
+        }
+
+        let enemies = encounter
+            .enemies
+            .iter()
+            .map(|key| {
+                let def = &self.enemies[key];
+                let s = &def.stats;
+                let mut actor = Actor::enemy_from_stats(
+                    &def.name, s.hp, s.hp, s.pp, s.pp, s.offense, s.defense, s.speed, s.iq,
+                );
+                actor.stats.guts = Stat::new(s.guts);
+                actor.affinities = Affinities::new(def.affinities.clone());
+                actor.stats.luck = Stat::new(s.luck);
+                actor.moves = resolve_moves(&def.name, &def.moves)?;
+                actor.sprite = Some(def.sprite.clone());
+                actor.rewards = Rewards {
+                    exp: def.exp,
+                    money: def.money,
+                    drops: def
+                        .drops
+                        .iter()
+                        .map(|(name, chance)| (item::find_by_name(name).unwrap(), *chance))
+                        .collect(),
+                };
+                Ok(actor.with_brain(build_brain(&def.brain)))
+            })
+            .collect::<Result<Vec<Actor>, DataError>>()?;
+
+        let track = match &encounter.music {
+            Some(track) => self.music.tracks[track],
+            None => TrackInfo::default(),
+        };
+        Ok(
+            BattleScene::new(allies, enemies, inventory, encounter.opening, seed)
+                .with_music(track, self.music.timing),
+        )
+    }
+}
@@ -0,0 +1,95 @@
+use crate::battle::element::{self, Element, ElementalDamage};
+use crate::battle::log::BattleLog;
+use crate::battle::rng::BattleRng;
+use crate::battle::stat::{self, fixed_damage, StatKind};
+use crate::battle::status::{self, StatusKind};
+use crate::battle::Actor;
+
+// What a special move or an item does to each of its targets.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Effect {
+    Damage(u16),
+    Heal(u16),
+    RestorePp(u16),
+    DrainPp(u16),
+    Revive(u16),
+    Buff(StatKind, i16),
+    // Status and how many turns it lasts.
+    Inflict(StatusKind, u16),
+    Cure(StatusKind),
+    CureAll,
+}
+
+impl Effect {
+    // Reviving is the only thing that can be done to a K.O. actor.
+    pub fn aims_ko_actors(&self) -> bool {
+        matches!(self, Effect::Revive(_))
+    }
+
+    pub fn apply(
+        &self,
+        element: Element,
+        target: &mut Actor,
+        rng: &mut BattleRng,
+        log: &mut BattleLog,
+    ) {
+        let is_ko = target.hp.is_ko();
+        if is_ko != self.aims_ko_actors() {
+            log.push(format!("It had no effect on {}.", target.name));
+            return;
+        }
+        match *self {
+            Effect::Damage(power) => {
+                match element::resolve(element, fixed_damage(power, rng), target, log) {
+                    ElementalDamage::Damage(dmg) => {
+                        log.push(format!("{} took {} damage.", target.name, dmg));
+                        target.take_damage(dmg, log);
+                    }
+                    ElementalDamage::Heal(amount) => {
+                        target.hp.heal(amount);
+                        log.push(format!("{} recovered {} HP.", target.name, amount));
+                    }
+                }
+            }
+            Effect::Heal(amount) => {
+                target.hp.heal(amount);
+                log.push(format!("{} recovered {} HP.", target.name, amount));
+            }
+            Effect::RestorePp(amount) => {
+                target.pp.heal(amount);
+                log.push(format!("{} recovered {} PP.", target.name, amount));
+            }
+            Effect::DrainPp(amount) => {
+                let drained = target.pp.drain(amount);
+                log.push(format!("{} lost {} PP.", target.name, drained));
+            }
+            Effect::Revive(amount) => {
+                target.hp.revive_to(amount);
+                log.push(format!("{} was revived!", target.name));
+            }
+            Effect::Buff(kind, levels) => {
+                let buffed = target.stats.get_mut(kind);
+                let at_limit = buffed.at_limit(levels);
+                let delta = buffed.buff(levels);
+                log.push(stat::describe_buff(
+                    &target.name,
+                    kind,
+                    levels,
+                    at_limit,
+                    delta,
+                ));
+            }
+            Effect::Inflict(kind, turns) => status::inflict(target, kind, turns, rng, log),
+            Effect::Cure(kind) => {
+                if !status::cure(target, kind, log) {
+                    log.push(format!("{} wasn't {}.", target.name, kind.name()));
+                }
+            }
+            Effect::CureAll => {
+                if !status::cure_all(target, log) {
+                    log.push(format!("{} was already fine.", target.name));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::battle::log::BattleLog;
+use crate::battle::Actor;
+use serde::Deserialize;
+
+// Elements and how each actor takes them. Damage goes through `resolve` right before being dealt.
+
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+pub enum Element {
+    // Not an element, affinities don't apply (healing, buffs...).
+    Neutral,
+    Physical,
+    Fire,
+    Freeze,
+    Thunder,
+    Flash,
+    Explosive,
+}
+
+impl Element {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Element::Neutral => "neutral",
+            Element::Physical => "physical",
+            Element::Fire => "fire",
+            Element::Freeze => "freeze",
+            Element::Thunder => "thunder",
+            Element::Flash => "flash",
+            Element::Explosive => "explosions",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+pub enum Affinity {
+    Weak,
+    Normal,
+    Resist,
+    Immune,
+    // The damage heals instead.
+    Absorb,
+}
+
+impl Affinity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Affinity::Weak => "weak",
+            Affinity::Normal => "normal",
+            Affinity::Resist => "resist",
+            Affinity::Immune => "immune",
+            Affinity::Absorb => "absorb",
+        }
+    }
+}
+
+// Anything not listed is Normal.
+#[derive(Clone, Default)]
+pub struct Affinities {
+    table: Vec<(Element, Affinity)>,
+    // Found out by hitting the actor with it, shown during the target selection.
+    revealed: Vec<Element>,
+}
+
+impl Affinities {
+    pub fn new(table: Vec<(Element, Affinity)>) -> Affinities {
+        Affinities {
+            table,
+            revealed: vec![],
+        }
+    }
+
+    pub fn get(&self, element: Element) -> Affinity {
+        self.table
+            .iter()
+            .find(|(e, _)| *e == element)
+            .map_or(Affinity::Normal, |(_, affinity)| *affinity)
+    }
+
+    pub fn table(&self) -> &[(Element, Affinity)] {
+        &self.table
+    }
+}
+
+pub enum ElementalDamage {
+    Damage(u16),
+    Heal(u16),
+}
+
+pub fn resolve(
+    element: Element,
+    damage: u16,
+    target: &mut Actor,
+    log: &mut BattleLog,
+) -> ElementalDamage {
+    let affinity = if element == Element::Neutral {
+        Affinity::Normal
+    } else {
+        target.affinities.get(element)
+    };
+    let (message, result) = match affinity {
+        Affinity::Normal => return ElementalDamage::Damage(damage),
+        Affinity::Weak => (
+            "is weak to",
+            ElementalDamage::Damage(damage.saturating_mul(2)),
+        ),
+        Affinity::Resist => ("resists", ElementalDamage::Damage(damage / 2)),
+        Affinity::Immune => ("is immune to", ElementalDamage::Damage(0)),
+        Affinity::Absorb => ("absorbs", ElementalDamage::Heal(damage)),
+    };
+    if !target.affinities.revealed.contains(&element) {
+        target.affinities.revealed.push(element);
+    }
+    log.push(format!("{} {} {}!", target.name, message, element.name()));
+    result
+}
+
+// " {weak: thunder, resist: freeze}" with what the party found out so far, or nothing.
+pub fn describe_known(actor: &Actor) -> String {
+    let known: Vec<String> = actor
+        .affinities
+        .revealed
+        .iter()
+        .map(|element| {
+            format!(
+                "{}: {}",
+                actor.affinities.get(*element).name(),
+                element.name()
+            )
+        })
+        .collect();
+    if known.is_empty() {
+        return String::new();
+    }
+    format!(" {{{}}}", known.join(", "))
+}
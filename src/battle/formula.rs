@@ -0,0 +1,234 @@
+use crate::battle::rng::BattleRng;
+
+// Physical damage formulas. Everything is computed on u32 with saturating operations, so any
+// combination of u16 stats gives a result in [minimum, u16::MAX].
+
+pub trait DamageFormula {
+    fn name(&self) -> &str;
+    // Damage before variance.
+    fn base(&self, offense: u16, attack_level: u16, defense: u16) -> u32;
+    // The final damage is within ±variance of the base, 0.25 is ±25%.
+    fn variance(&self) -> f32;
+    // Applied after variance.
+    fn minimum(&self) -> u16;
+}
+
+pub fn roll(
+    formula: &dyn DamageFormula,
+    offense: u16,
+    attack_level: u16,
+    defense: u16,
+    rng: &mut BattleRng,
+) -> u16 {
+    let base = formula.base(offense, attack_level, defense) as f32;
+    let variance = formula.variance().clamp(0., 1.);
+    let multiplier = if variance > 0. {
+        rng.gen_range((1. - variance)..=(1. + variance))
+    } else {
+        1.
+    };
+    // Float to int casts saturate, no need to clamp to u16::MAX by hand.
+    let damage = (base * multiplier).round() as u16;
+    damage.max(formula.minimum())
+}
+
+fn scaled(offense: u16, attack_level: u16, factor: u32) -> u32 {
+    u32::from(offense)
+        .saturating_mul(u32::from(attack_level))
+        .saturating_mul(factor)
+}
+
+// Twice the offense against the defense, weak hits can do nothing at all.
+pub struct Mother1 {
+    pub variance: f32,
+}
+
+impl Default for Mother1 {
+    fn default() -> Mother1 {
+        Mother1 { variance: 0.25 }
+    }
+}
+
+impl DamageFormula for Mother1 {
+    fn name(&self) -> &str {
+        "Mother 1"
+    }
+
+    fn base(&self, offense: u16, attack_level: u16, defense: u16) -> u32 {
+        scaled(offense, attack_level, 2).saturating_sub(u32::from(defense))
+    }
+
+    fn variance(&self) -> f32 {
+        self.variance
+    }
+
+    fn minimum(&self) -> u16 {
+        0
+    }
+}
+
+// Attack level times offense minus defense, always at least 1. The original formula of the game.
+pub struct Mother2 {
+    pub variance: f32,
+}
+
+impl Default for Mother2 {
+    fn default() -> Mother2 {
+        Mother2 { variance: 0.25 }
+    }
+}
+
+impl DamageFormula for Mother2 {
+    fn name(&self) -> &str {
+        "Mother 2"
+    }
+
+    fn base(&self, offense: u16, attack_level: u16, defense: u16) -> u32 {
+        scaled(offense, attack_level, 1).saturating_sub(u32::from(defense))
+    }
+
+    fn variance(&self) -> f32 {
+        self.variance
+    }
+
+    fn minimum(&self) -> u16 {
+        1
+    }
+}
+
+// Defense only counts for half, with a tighter spread.
+pub struct Mother3 {
+    pub variance: f32,
+}
+
+impl Default for Mother3 {
+    fn default() -> Mother3 {
+        Mother3 { variance: 0.1 }
+    }
+}
+
+impl DamageFormula for Mother3 {
+    fn name(&self) -> &str {
+        "Mother 3"
+    }
+
+    fn base(&self, offense: u16, attack_level: u16, defense: u16) -> u32 {
+        scaled(offense, attack_level, 1).saturating_sub(u32::from(defense) / 2)
+    }
+
+    fn variance(&self) -> f32 {
+        self.variance
+    }
+
+    fn minimum(&self) -> u16 {
+        1
+    }
+}
+
+// For `--formula`.
+pub fn preset(name: &str) -> Option<Box<dyn DamageFormula>> {
+    match name {
+        "mother1" => Some(Box::new(Mother1::default())),
+        "mother2" => Some(Box::new(Mother2::default())),
+        "mother3" => Some(Box::new(Mother3::default())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDARIES: [u16; 6] = [0, 1, 2, 255, 1000, u16::MAX];
+
+    fn presets() -> Vec<Box<dyn DamageFormula>> {
+        ["mother1", "mother2", "mother3"]
+            .iter()
+            .map(|name| preset(name).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn full_stat_range_stays_in_bounds() {
+        let mut rng = BattleRng::from_seed(1);
+        for formula in presets() {
+            for &offense in BOUNDARIES.iter() {
+                for &attack_level in BOUNDARIES.iter() {
+                    for &defense in BOUNDARIES.iter() {
+                        let base = formula.base(offense, attack_level, defense);
+                        let damage =
+                            roll(formula.as_ref(), offense, attack_level, defense, &mut rng);
+                        assert!(damage >= formula.minimum(), "{}", formula.name());
+                        if base == 0 {
+                            assert_eq!(damage, formula.minimum(), "{}", formula.name());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn huge_stats_saturate() {
+        let mut rng = BattleRng::from_seed(2);
+        for formula in presets() {
+            for &defense in [0, u16::MAX].iter() {
+                let damage = roll(formula.as_ref(), u16::MAX, u16::MAX, defense, &mut rng);
+                assert_eq!(damage, u16::MAX, "{}", formula.name());
+            }
+        }
+    }
+
+    #[test]
+    fn defense_above_offense_gives_the_minimum() {
+        let mut rng = BattleRng::from_seed(3);
+        for formula in presets() {
+            assert_eq!(formula.base(1, 1, u16::MAX), 0);
+            let damage = roll(formula.as_ref(), 1, 1, u16::MAX, &mut rng);
+            assert_eq!(damage, formula.minimum(), "{}", formula.name());
+        }
+    }
+
+    #[test]
+    fn attack_level_zero_does_nothing() {
+        for formula in presets() {
+            assert_eq!(formula.base(u16::MAX, 0, 0), 0, "{}", formula.name());
+            assert_eq!(formula.base(0, u16::MAX, 0), 0, "{}", formula.name());
+        }
+    }
+
+    #[test]
+    fn no_variance_gives_the_exact_base() {
+        let mut rng = BattleRng::from_seed(4);
+        let formulas: Vec<(Box<dyn DamageFormula>, u16)> = vec![
+            (Box::new(Mother1 { variance: 0. }), 2 * 20 * 3 - 10),
+            (Box::new(Mother2 { variance: 0. }), 20 * 3 - 10),
+            (Box::new(Mother3 { variance: 0. }), 20 * 3 - 5),
+        ];
+        for (formula, expected) in formulas.iter() {
+            assert_eq!(formula.base(20, 3, 10), u32::from(*expected));
+            for _ in 0..100 {
+                assert_eq!(roll(formula.as_ref(), 20, 3, 10, &mut rng), *expected);
+            }
+        }
+    }
+
+    #[test]
+    fn bad_variance_is_clamped() {
+        let mut rng = BattleRng::from_seed(5);
+        for &variance in [f32::NAN, -1., f32::NEG_INFINITY].iter() {
+            let formula = Mother2 { variance };
+            for _ in 0..100 {
+                assert_eq!(roll(&formula, 20, 3, 10, &mut rng), 50);
+            }
+        }
+        // Counts as ±100%.
+        for &variance in [5., f32::INFINITY].iter() {
+            let formula = Mother2 { variance };
+            for _ in 0..100 {
+                let damage = roll(&formula, 20, 3, 10, &mut rng);
+                assert!((1..=100).contains(&damage));
+            }
+        }
+    }
+}
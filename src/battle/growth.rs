@@ -0,0 +1,283 @@
+use crate::battle::special_move::{self, MoveId};
+use crate::battle::stat::StatBlock;
+use crate::battle::Actor;
+use serde::Deserialize;
+
+// Character levels. Stats are always recomputed from the level-1 base, the growth rates and the
+// equipment, so leveling up never accumulates rounding errors.
+
+pub const MAX_LEVEL: u16 = 99;
+// Every point of vitality adds that much max HP, whether it's from the base, levels or equipment.
+const HP_PER_VITALITY: u16 = 2;
+
+// Gained per level, fractional so slow stats still go up every few levels.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct Growth {
+    pub hp: f32,
+    pub pp: f32,
+    pub offense: f32,
+    pub defense: f32,
+    pub speed: f32,
+    pub iq: f32,
+    pub guts: f32,
+    pub vitality: f32,
+    pub luck: f32,
+}
+
+#[derive(Clone)]
+pub struct Progression {
+    pub level: u16,
+    // Total EXP since level 1.
+    pub exp: u32,
+    // Stats at level 1.
+    pub base: StatBlock,
+    pub growth: Growth,
+    // Flat bonus from whatever is equipped.
+    pub equipment: StatBlock,
+    // Special moves and the level they are learned at.
+    pub learnset: Vec<(u16, MoveId)>,
+}
+
+// Total EXP needed to reach a level.
+pub fn exp_for_level(level: u16) -> u32 {
+    let steps = u32::from(level.saturating_sub(1));
+    6 * steps * steps
+}
+
+impl Progression {
+    pub fn new(
+        level: u16,
+        base: StatBlock,
+        growth: Growth,
+        equipment: StatBlock,
+        learnset: Vec<(u16, MoveId)>,
+    ) -> Progression {
+        let level = level.clamp(1, MAX_LEVEL);
+        Progression {
+            level,
+            exp: exp_for_level(level),
+            base,
+            growth,
+            equipment,
+            learnset,
+        }
+    }
+
+    pub fn stats(&self) -> StatBlock {
+        self.stats_at(self.level)
+    }
+
+    fn stats_at(&self, level: u16) -> StatBlock {
+        let steps = f32::from(level - 1);
+        let grow = |base: u16, rate: f32, bonus: u16| {
+            base.saturating_add((rate * steps) as u16)
+                .saturating_add(bonus)
+        };
+        let vitality = grow(
+            self.base.vitality,
+            self.growth.vitality,
+            self.equipment.vitality,
+        );
+        StatBlock {
+            hp: grow(self.base.hp, self.growth.hp, self.equipment.hp)
+                .saturating_add(vitality.saturating_mul(HP_PER_VITALITY)),
+            pp: grow(self.base.pp, self.growth.pp, self.equipment.pp),
+            offense: grow(
+                self.base.offense,
+                self.growth.offense,
+                self.equipment.offense,
+            ),
+            defense: grow(
+                self.base.defense,
+                self.growth.defense,
+                self.equipment.defense,
+            ),
+            speed: grow(self.base.speed, self.growth.speed, self.equipment.speed),
+            iq: grow(self.base.iq, self.growth.iq, self.equipment.iq),
+            guts: grow(self.base.guts, self.growth.guts, self.equipment.guts),
+            vitality,
+            luck: grow(self.base.luck, self.growth.luck, self.equipment.luck),
+        }
+    }
+
+    // Everything learned up to the current level.
+    pub fn known_moves(&self) -> Vec<MoveId> {
+        self.learnset
+            .iter()
+            .filter(|(level, _)| *level <= self.level)
+            .map(|(_, id)| *id)
+            .collect()
+    }
+}
+
+// Adds the EXP and applies every level reached on the way. Returns the lines for the victory
+// screen, nothing for enemies.
+pub fn gain_exp(actor: &mut Actor, exp: u32) -> Vec<String> {
+    let Actor {
+        name,
+        hp,
+        pp,
+        stats,
+        moves,
+        progression,
+        ..
+    } = actor;
+    let progression = match progression {
+        Some(progression) => progression,
+        None => return vec![],
+    };
+
+    progression.exp = progression.exp.saturating_add(exp);
+    let mut lines = vec![];
+    while progression.level < MAX_LEVEL && progression.exp >= exp_for_level(progression.level + 1) {
+        let before = progression.stats();
+        progression.level += 1;
+        let after = progression.stats();
+        lines.push(format!("{} reached level {}!", name, progression.level));
+
+        let gains = [
+            ("Max HP", before.hp, after.hp),
+            ("Max PP", before.pp, after.pp),
+            ("Offense", before.offense, after.offense),
+            ("Defense", before.defense, after.defense),
+            ("Speed", before.speed, after.speed),
+            ("IQ", before.iq, after.iq),
+            ("Guts", before.guts, after.guts),
+            ("Vitality", before.vitality, after.vitality),
+            ("Luck", before.luck, after.luck),
+        ];
+        for (stat, old, new) in gains.iter() {
+            if new > old {
+                lines.push(format!("{} went up by {}.", stat, new - old));
+            }
+        }
+
+        // The new max comes with as many fresh points.
+        hp.set_max(after.hp);
+        hp.heal(after.hp - before.hp);
+        pp.set_max(after.pp);
+        pp.heal(after.pp - before.pp);
+        // Modifiers stay, they are relative to the base.
+        stats.offense.base = after.offense;
+        stats.defense.base = after.defense;
+        stats.speed.base = after.speed;
+        stats.iq.base = after.iq;
+        stats.guts.base = after.guts;
+        stats.vitality.base = after.vitality;
+        stats.luck.base = after.luck;
+
+        for (level, id) in progression.learnset.iter() {
+            if *level == progression.level && !moves.contains(id) {
+                moves.push(*id);
+                lines.push(format!(
+                    "{} learned {}!",
+                    name,
+                    special_move::get(*id).display_name()
+                ));
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn character(level: u16, learnset: Vec<(u16, MoveId)>) -> Actor {
+        let base = StatBlock {
+            hp: 50,
+            pp: 10,
+            offense: 20,
+            defense: 10,
+            speed: 10,
+            iq: 5,
+            guts: 0,
+            vitality: 5,
+            luck: 0,
+        };
+        let growth = Growth {
+            hp: 6.,
+            pp: 2.5,
+            offense: 3.,
+            defense: 2.,
+            speed: 1.,
+            iq: 0.5,
+            guts: 0.,
+            vitality: 1.,
+            luck: 0.,
+        };
+        let progression = Progression::new(level, base, growth, StatBlock::default(), learnset);
+        Actor::character("One", progression)
+    }
+
+    #[test]
+    fn exp_curve() {
+        assert_eq!(exp_for_level(0), 0);
+        assert_eq!(exp_for_level(1), 0);
+        assert_eq!(exp_for_level(2), 6);
+        assert_eq!(exp_for_level(10), 486);
+        assert_eq!(exp_for_level(MAX_LEVEL), 6 * 98 * 98);
+    }
+
+    #[test]
+    fn several_levels_at_once() {
+        let mut actor = character(1, vec![]);
+        let lines = gain_exp(&mut actor, exp_for_level(4) + 1);
+        let progression = actor.progression.as_ref().unwrap();
+        assert_eq!(progression.level, 4);
+        assert_eq!(progression.exp, exp_for_level(4) + 1);
+        for level in 2..=4 {
+            assert!(lines.contains(&format!("One reached level {}!", level)));
+        }
+        // Same as a character starting at level 4.
+        let stats = character(4, vec![]).progression.unwrap().stats();
+        // Healed by what was gained, the meters roll up to it.
+        assert_eq!(actor.hp.current_and_max().1, stats.hp);
+        assert_eq!(actor.hp.target(), stats.hp);
+        assert_eq!(actor.pp.target(), stats.pp);
+        assert_eq!(actor.stats.offense.base, stats.offense);
+        // Vitality 8 by then, 2 HP each.
+        assert_eq!(stats.hp, 50 + 18 + 16);
+        // 2.5 per level, rounded down.
+        assert_eq!(stats.pp, 10 + 7);
+    }
+
+    #[test]
+    fn capped_at_max_level() {
+        let mut actor = character(MAX_LEVEL - 1, vec![]);
+        gain_exp(&mut actor, u32::MAX);
+        let progression = actor.progression.as_ref().unwrap();
+        assert_eq!(progression.level, MAX_LEVEL);
+        assert_eq!(progression.exp, u32::MAX);
+        assert!(gain_exp(&mut actor, 1000).is_empty());
+        assert_eq!(
+            Progression::new(
+                500,
+                StatBlock::default(),
+                Growth::default(),
+                StatBlock::default(),
+                vec![]
+            )
+            .level,
+            MAX_LEVEL
+        );
+    }
+
+    #[test]
+    fn moves_unlock_with_levels() {
+        let fire = special_move::find_by_name("PK Fire α").unwrap();
+        let freeze = special_move::find_by_name("PK Freeze α").unwrap();
+        let mut actor = character(1, vec![(1, fire), (3, freeze)]);
+        assert_eq!(actor.moves, vec![fire]);
+
+        let lines = gain_exp(&mut actor, exp_for_level(2));
+        assert_eq!(actor.moves, vec![fire]);
+        assert!(!lines.iter().any(|line| line.contains("learned")));
+
+        let lines = gain_exp(&mut actor, exp_for_level(3) - exp_for_level(2));
+        assert_eq!(actor.moves, vec![fire, freeze]);
+        assert!(lines.contains(&String::from("One learned PK Freeze α!")));
+    }
+}
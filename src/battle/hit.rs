@@ -0,0 +1,82 @@
+use crate::battle::action::Caster;
+use crate::battle::rng::BattleRng;
+use crate::battle::stat;
+use crate::battle::status::{self, StatusKind};
+use crate::battle::Actor;
+
+// Whether an action connects, rolled for each target before anything else happens.
+
+// Base accuracy of an action.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Accuracy {
+    // Never misses: healing, most PSI, items.
+    Sure,
+    // Might miss, and the target might dodge.
+    Physical(f32),
+    // Might miss, but there is nothing to dodge.
+    Mental(f32),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HitOutcome {
+    Hit,
+    Miss,
+    Dodged,
+}
+
+impl HitOutcome {
+    pub fn connects(self) -> bool {
+        self == HitOutcome::Hit
+    }
+
+    // What the turn log says about it, nothing when it connects.
+    pub fn describe(self, caster: &str, target: &str) -> Option<String> {
+        match self {
+            HitOutcome::Hit => None,
+            HitOutcome::Miss => Some(format!("{} missed {}!", caster, target)),
+            HitOutcome::Dodged => Some(format!("{} dodged quickly!", target)),
+        }
+    }
+}
+
+fn caster_has(caster: &Caster, kind: StatusKind) -> bool {
+    caster.statuses.iter().any(|s| s.kind == kind)
+}
+
+// Can't see much through the tears, and confusion doesn't help either.
+fn accuracy_factor(caster: &Caster) -> f32 {
+    let mut factor = 1.;
+    if caster_has(caster, StatusKind::Crying) {
+        factor *= 0.5;
+    }
+    if caster_has(caster, StatusKind::Confusion) {
+        factor *= 0.75;
+    }
+    factor
+}
+
+pub fn resolve(
+    accuracy: Accuracy,
+    caster: &Caster,
+    target: &Actor,
+    rng: &mut BattleRng,
+) -> HitOutcome {
+    let chance = match accuracy {
+        Accuracy::Sure => return HitOutcome::Hit,
+        Accuracy::Physical(chance) | Accuracy::Mental(chance) => chance,
+    };
+    if !rng.chance(chance * accuracy_factor(caster)) {
+        return HitOutcome::Miss;
+    }
+    if let Accuracy::Physical(_) = accuracy {
+        let dodge = stat::dodge_chance(
+            target.stats.speed.multiplied(),
+            caster.stats.speed.multiplied(),
+            target.stats.luck.multiplied(),
+        );
+        if status::can_dodge(target) && rng.chance(dodge) {
+            return HitOutcome::Dodged;
+        }
+    }
+    HitOutcome::Hit
+}
@@ -0,0 +1,106 @@
+use crate::battle::action_decision::CharacterTurnDecisionState;
+use crate::battle::log::BattleLog;
+use crate::battle::rng::BattleRng;
+use crate::battle::stat::first_strike_chance;
+use crate::battle::turn_preparation::TurnPreparationState;
+use crate::battle::{Actor, MacroBattleStates, Team, UIAction};
+use crate::battle::{BattleState, BattleStateTransition};
+use crate::timer::Timer;
+use crate::{Assets, BattleScene};
+use serde::Deserialize;
+use tetra::graphics::text::Text;
+use tetra::graphics::{Color, DrawParams};
+use tetra::math::Vec2;
+use tetra::Context;
+
+// Who gets a free opening turn. Encounters can force it (scripted ambushes and the like),
+// otherwise speed decides.
+#[derive(Clone, Copy, Deserialize, Default)]
+pub enum Opening {
+    #[default]
+    BySpeed,
+    PartyStrikesFirst,
+    EnemiesStrikeFirst,
+    // Nobody gets a free turn.
+    Even,
+}
+
+// The fade-in and the small "X and its cohort jumped in the way!" message.
+pub struct IntroState {
+    time: Timer,
+}
+
+impl IntroState {
+    // Announces the encounter and settles the first strike, which team gets it if any.
+    pub fn start(
+        allies: &[Actor],
+        enemies: &[Actor],
+        opening: Opening,
+        rng: &mut BattleRng,
+        log: &mut BattleLog,
+    ) -> (IntroState, Option<Team>) {
+        let leader = &enemies[0].name;
+        let announcement = if enemies.len() > 1 {
+            format!("{} and its cohort jumped in the way!", leader)
+        } else {
+            format!("{} jumped in the way!", leader)
+        };
+        log.push(announcement);
+
+        let party_speed = BattleScene::average_speed(allies);
+        let enemy_speed = BattleScene::average_speed(enemies);
+        let first_strike = match opening {
+            Opening::BySpeed => {
+                if rng.chance(first_strike_chance(party_speed, enemy_speed)) {
+                    Some(Team::Ally)
+                } else if rng.chance(first_strike_chance(enemy_speed, party_speed)) {
+                    Some(Team::Enemy)
+                } else {
+                    None
+                }
+            }
+            Opening::PartyStrikesFirst => Some(Team::Ally),
+            Opening::EnemiesStrikeFirst => Some(Team::Enemy),
+            Opening::Even => None,
+        };
+        match first_strike {
+            Some(Team::Ally) => log.push(String::from("The party caught them off guard!")),
+            Some(Team::Enemy) => log.push(String::from("The enemies attacked by surprise!")),
+            None => (),
+        }
+
+        let intro = IntroState {
+            time: Timer::new(1.5),
+        };
+        (intro, first_strike)
+    }
+
+    pub fn draw(_scene: &BattleScene, ctx: &mut Context, assets: &Assets) {
+        let mut debug_text = Text::new("--Intro--\n", assets.headupdaisy.clone());
+        debug_text.draw(
+            ctx,
+            DrawParams::new()
+                .color(Color::rgb8(0xeb, 0xdb, 0xb2))
+                .position(Vec2::new(16., 360.)),
+        );
+    }
+}
+
+impl BattleState for IntroState {
+    fn update(scene: &mut BattleScene, dt: f32, input: &[UIAction]) -> BattleStateTransition {
+        if let MacroBattleStates::Intro(intro) = &mut scene.state {
+            intro.time.tick(dt);
+            if !intro.time.done() && !input.contains(&UIAction::Validate) {
+                return None;
+            }
+            // Enemies being faster means the party doesn't get to choose anything this turn.
+            return match scene.first_strike {
+                Some(Team::Enemy) => {
+                    Some(MacroBattleStates::TurnPreparation(TurnPreparationState {}))
+                }
+                _ => Some(CharacterTurnDecisionState::new_turn(&scene.allies)),
+            };
+        }
+        None
+    }
+}
@@ -0,0 +1,172 @@
+use crate::battle::effect::Effect;
+use crate::battle::element::Element;
+use crate::battle::special_move::TargetRule;
+use crate::battle::stat::StatKind;
+use crate::battle::status::StatusKind;
+use crate::battle::turn_order::Priority;
+
+// Consumables. Like special moves, the catalog is static and referred to by index.
+pub type ItemId = usize;
+
+pub struct Item {
+    pub name: &'static str,
+    pub target: TargetRule,
+    pub element: Element,
+    pub priority: Priority,
+    pub effect: Effect,
+}
+
+pub const CATALOG: &[Item] = &[
+    Item {
+        name: "Cookie",
+        target: TargetRule::SingleAlly,
+        element: Element::Neutral,
+        priority: Priority::First,
+        effect: Effect::Heal(30),
+    },
+    Item {
+        name: "Hamburger",
+        target: TargetRule::SingleAlly,
+        element: Element::Neutral,
+        priority: Priority::Normal,
+        effect: Effect::Heal(100),
+    },
+    Item {
+        name: "Magic Truffle",
+        target: TargetRule::SingleAlly,
+        element: Element::Neutral,
+        priority: Priority::First,
+        effect: Effect::RestorePp(20),
+    },
+    Item {
+        name: "Horn of Life",
+        target: TargetRule::SingleAlly,
+        element: Element::Neutral,
+        priority: Priority::Normal,
+        effect: Effect::Revive(50),
+    },
+    Item {
+        name: "Defense Spray",
+        target: TargetRule::SingleAlly,
+        element: Element::Neutral,
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Defense, 1),
+    },
+    Item {
+        name: "Skip Sandwich",
+        target: TargetRule::SingleAlly,
+        element: Element::Neutral,
+        priority: Priority::First,
+        effect: Effect::Buff(StatKind::Speed, 1),
+    },
+    Item {
+        name: "IQ Capsule",
+        target: TargetRule::SingleAlly,
+        element: Element::Neutral,
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Iq, 1),
+    },
+    Item {
+        name: "Sticky Slime",
+        target: TargetRule::SingleEnemy,
+        element: Element::Neutral,
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Speed, -1),
+    },
+    Item {
+        name: "Bottle Rocket",
+        target: TargetRule::SingleEnemy,
+        element: Element::Explosive,
+        priority: Priority::Normal,
+        effect: Effect::Damage(60),
+    },
+    Item {
+        name: "Bomb",
+        target: TargetRule::AllEnemies,
+        element: Element::Explosive,
+        priority: Priority::Normal,
+        effect: Effect::Damage(45),
+    },
+    Item {
+        name: "Herbal Tea",
+        target: TargetRule::SingleAlly,
+        element: Element::Neutral,
+        priority: Priority::Normal,
+        effect: Effect::Inflict(StatusKind::Calm, 3),
+    },
+    Item {
+        name: "Antidote",
+        target: TargetRule::SingleAlly,
+        element: Element::Neutral,
+        priority: Priority::Normal,
+        effect: Effect::Cure(StatusKind::Poison),
+    },
+    Item {
+        name: "Stink Bomb",
+        target: TargetRule::AllEnemies,
+        element: Element::Neutral,
+        priority: Priority::Normal,
+        effect: Effect::Inflict(StatusKind::Poison, 4),
+    },
+    Item {
+        name: "Tear Gas",
+        target: TargetRule::AllEnemies,
+        element: Element::Neutral,
+        priority: Priority::Normal,
+        effect: Effect::Inflict(StatusKind::Crying, 3),
+    },
+];
+
+pub fn get(id: ItemId) -> &'static Item {
+    &CATALOG[id]
+}
+
+pub fn find_by_name(name: &str) -> Option<ItemId> {
+    CATALOG.iter().position(|i| i.name == name)
+}
+
+// The party's bag. Stacks are kept in insertion order so the menu doesn't shuffle around.
+#[derive(Default)]
+pub struct Inventory {
+    stacks: Vec<(ItemId, u16)>,
+}
+
+impl Inventory {
+    pub fn add(&mut self, id: ItemId, count: u16) {
+        match self.stacks.iter_mut().find(|(item, _)| *item == id) {
+            Some((_, stack_count)) => *stack_count = stack_count.saturating_add(count),
+            None => self.stacks.push((id, count)),
+        }
+    }
+
+    // Returns false if there was none left.
+    pub fn remove(&mut self, id: ItemId) -> bool {
+        match self.stacks.iter().position(|(item, _)| *item == id) {
+            Some(index) => {
+                self.stacks[index].1 -= 1;
+                if self.stacks[index].1 == 0 {
+                    self.stacks.remove(index);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn stacks(&self) -> &[(ItemId, u16)] {
+        &self.stacks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+
+    // "Cookie x2, Bomb x1", for outside of the battle menus.
+    pub fn describe(&self) -> String {
+        self.stacks
+            .iter()
+            .map(|(id, count)| format!("{} x{}", get(*id).name, count))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
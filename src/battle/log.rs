@@ -0,0 +1,16 @@
+// What happened during the fight, in order. Also echoed on stdout for debugging.
+#[derive(Default)]
+pub struct BattleLog {
+    lines: Vec<String>,
+}
+
+impl BattleLog {
+    pub fn push(&mut self, message: String) {
+        println!("{}", message);
+        self.lines.push(message);
+    }
+
+    pub fn last_lines(&self, count: usize) -> &[String] {
+        &self.lines[self.lines.len().saturating_sub(count)..]
+    }
+}
@@ -0,0 +1,313 @@
+use serde::Deserialize;
+
+// Combo bashes: after the first hit, pressing on the beat of the battle track adds another hit.
+// Everything here works on plain seconds from a BeatClock, the audio side only has to provide
+// the playback position.
+
+pub const MAX_COMBO_HITS: u8 = 16;
+
+// Tempo of a battle track, from music.ron.
+#[derive(Clone, Copy, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TrackInfo {
+    pub bpm: f32,
+    // When the first beat happens, in seconds from the start of the track.
+    #[serde(default)]
+    pub offset: f32,
+}
+
+impl Default for TrackInfo {
+    fn default() -> TrackInfo {
+        TrackInfo {
+            bpm: 120.,
+            offset: 0.,
+        }
+    }
+}
+
+impl TrackInfo {
+    fn period(&self) -> f32 {
+        60. / self.bpm
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, Debug)]
+#[serde(deny_unknown_fields, default)]
+pub struct RhythmConfig {
+    // How far from the beat a press still counts, in seconds, on both sides.
+    pub window: f32,
+    // Time between the sound being played and being heard, removed from the clock before judging.
+    pub latency: f32,
+}
+
+impl Default for RhythmConfig {
+    fn default() -> RhythmConfig {
+        RhythmConfig {
+            window: 0.1,
+            latency: 0.,
+        }
+    }
+}
+
+pub trait BeatClock {
+    fn advance(&mut self, dt: f32);
+    // Seconds since the track started.
+    fn time(&self) -> f32;
+    fn track(&self) -> TrackInfo;
+}
+
+// Counts time on its own instead of asking the audio playback. Used until there is battle music,
+// and to drive the combo logic step by step without any audio.
+pub struct SyntheticClock {
+    time: f32,
+    track: TrackInfo,
+}
+
+impl SyntheticClock {
+    pub fn new(track: TrackInfo) -> SyntheticClock {
+        SyntheticClock { time: 0., track }
+    }
+}
+
+impl BeatClock for SyntheticClock {
+    fn advance(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn track(&self) -> TrackInfo {
+        self.track
+    }
+}
+
+// What a timed action sees of the music and the player for one frame.
+#[derive(Clone, Copy)]
+pub struct BeatInput {
+    pub time: f32,
+    pub track: TrackInfo,
+    pub config: RhythmConfig,
+    pub pressed: bool,
+}
+
+impl BeatInput {
+    // Heard time in beats since the first one.
+    fn beats(&self) -> f32 {
+        (self.time - self.config.latency - self.track.offset) / self.track.period()
+    }
+
+    fn window_in_beats(&self) -> f32 {
+        self.config.window / self.track.period()
+    }
+}
+
+pub enum ComboStep {
+    // Waiting for the next beat.
+    Wait,
+    // On beat, one more hit.
+    Hit,
+    // Off beat, missed beat or no hits left.
+    Over,
+}
+
+pub struct Combo {
+    hits: u8,
+    last_beat: i64,
+}
+
+impl Combo {
+    // The first hit counts as the closest beat, the next press goes for the one after.
+    pub fn start(beat: &BeatInput) -> Combo {
+        Combo {
+            hits: 1,
+            last_beat: beat.beats().round() as i64,
+        }
+    }
+
+    pub fn hits(&self) -> u8 {
+        self.hits
+    }
+
+    pub fn update(&mut self, beat: &BeatInput) -> ComboStep {
+        if self.hits >= MAX_COMBO_HITS {
+            return ComboStep::Over;
+        }
+        let beats = beat.beats();
+        let window = beat.window_in_beats();
+        if beat.pressed {
+            let closest = beats.round();
+            // Pressing twice on the same beat is as bad as pressing off beat.
+            if closest as i64 > self.last_beat && (beats - closest).abs() <= window {
+                self.last_beat = closest as i64;
+                self.hits += 1;
+                return ComboStep::Hit;
+            }
+            return ComboStep::Over;
+        }
+        if beats > (self.last_beat + 1) as f32 + window {
+            return ComboStep::Over;
+        }
+        ComboStep::Wait
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 120 BPM: a beat every half second, the window is a fifth of a beat.
+    fn new_clock(offset: f32) -> SyntheticClock {
+        SyntheticClock::new(TrackInfo { bpm: 120., offset })
+    }
+
+    fn input(clock: &SyntheticClock, config: RhythmConfig, pressed: bool) -> BeatInput {
+        BeatInput {
+            time: clock.time(),
+            track: clock.track(),
+            config,
+            pressed,
+        }
+    }
+
+    // Moves the clock to the given time and presses there.
+    fn press_at(combo: &mut Combo, clock: &mut SyntheticClock, time: f32) -> ComboStep {
+        clock.advance(time - clock.time());
+        combo.update(&input(clock, RhythmConfig::default(), true))
+    }
+
+    fn start_at(clock: &mut SyntheticClock, time: f32, config: RhythmConfig) -> Combo {
+        clock.advance(time - clock.time());
+        Combo::start(&input(clock, config, true))
+    }
+
+    #[test]
+    fn on_beat_presses_add_hits() {
+        let mut clock = new_clock(0.);
+        let mut combo = start_at(&mut clock, 0.5, RhythmConfig::default());
+        assert!(matches!(
+            press_at(&mut combo, &mut clock, 1.05),
+            ComboStep::Hit
+        ));
+        assert!(matches!(
+            press_at(&mut combo, &mut clock, 1.42),
+            ComboStep::Hit
+        ));
+        assert_eq!(combo.hits(), 3);
+    }
+
+    #[test]
+    fn waiting_between_beats() {
+        let mut clock = new_clock(0.);
+        let mut combo = start_at(&mut clock, 0.5, RhythmConfig::default());
+        clock.advance(0.3);
+        let step = combo.update(&input(&clock, RhythmConfig::default(), false));
+        assert!(matches!(step, ComboStep::Wait));
+        assert_eq!(combo.hits(), 1);
+    }
+
+    #[test]
+    fn off_beat_press_ends_the_combo() {
+        let mut clock = new_clock(0.);
+        let mut combo = start_at(&mut clock, 0.5, RhythmConfig::default());
+        assert!(matches!(
+            press_at(&mut combo, &mut clock, 0.75),
+            ComboStep::Over
+        ));
+        assert_eq!(combo.hits(), 1);
+    }
+
+    #[test]
+    fn pressing_twice_on_a_beat_ends_the_combo() {
+        let mut clock = new_clock(0.);
+        let mut combo = start_at(&mut clock, 0.5, RhythmConfig::default());
+        // Same beat as the first hit.
+        assert!(matches!(
+            press_at(&mut combo, &mut clock, 0.55),
+            ComboStep::Over
+        ));
+
+        let mut combo = start_at(&mut clock, 1.5, RhythmConfig::default());
+        assert!(matches!(
+            press_at(&mut combo, &mut clock, 2.),
+            ComboStep::Hit
+        ));
+        assert!(matches!(
+            press_at(&mut combo, &mut clock, 2.05),
+            ComboStep::Over
+        ));
+        assert_eq!(combo.hits(), 2);
+    }
+
+    #[test]
+    fn missing_a_beat_ends_the_combo() {
+        let mut clock = new_clock(0.);
+        let mut combo = start_at(&mut clock, 0.5, RhythmConfig::default());
+        // Still inside the next beat's window.
+        clock.advance(0.55);
+        let step = combo.update(&input(&clock, RhythmConfig::default(), false));
+        assert!(matches!(step, ComboStep::Wait));
+        clock.advance(0.1);
+        let step = combo.update(&input(&clock, RhythmConfig::default(), false));
+        assert!(matches!(step, ComboStep::Over));
+    }
+
+    #[test]
+    fn latency_shifts_the_judged_beat() {
+        let late = RhythmConfig {
+            latency: 0.2,
+            ..RhythmConfig::default()
+        };
+        // Heard at 0.5 and 1.0.
+        let mut clock = new_clock(0.);
+        let mut combo = start_at(&mut clock, 0.7, late);
+        clock.advance(0.5);
+        assert!(matches!(
+            combo.update(&input(&clock, late, true)),
+            ComboStep::Hit
+        ));
+
+        // The same presses without latency are off beat.
+        let mut clock = new_clock(0.);
+        let mut combo = start_at(&mut clock, 0.5, RhythmConfig::default());
+        assert!(matches!(
+            press_at(&mut combo, &mut clock, 1.2),
+            ComboStep::Over
+        ));
+    }
+
+    #[test]
+    fn offset_shifts_the_beats() {
+        // Beats at 0.25, 0.75, 1.25...
+        let mut clock = new_clock(0.25);
+        let mut combo = start_at(&mut clock, 0.75, RhythmConfig::default());
+        assert!(matches!(
+            press_at(&mut combo, &mut clock, 1.25),
+            ComboStep::Hit
+        ));
+
+        let mut clock = new_clock(0.);
+        let mut combo = start_at(&mut clock, 0.5, RhythmConfig::default());
+        assert!(matches!(
+            press_at(&mut combo, &mut clock, 0.75),
+            ComboStep::Over
+        ));
+    }
+
+    #[test]
+    fn combo_stops_at_max_hits() {
+        let mut clock = new_clock(0.);
+        let mut combo = start_at(&mut clock, 0.5, RhythmConfig::default());
+        let mut beat = 1.;
+        loop {
+            beat += 1.;
+            match press_at(&mut combo, &mut clock, beat * 0.5) {
+                ComboStep::Hit => assert!(combo.hits() <= MAX_COMBO_HITS),
+                ComboStep::Over => break,
+                ComboStep::Wait => panic!("pressed without a result"),
+            }
+        }
+        assert_eq!(combo.hits(), MAX_COMBO_HITS);
+    }
+}
@@ -0,0 +1,29 @@
+use rand::distributions::uniform::{SampleRange, SampleUniform};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Every random roll of a battle goes through this one. Same seed + same inputs = same fight.
+pub struct BattleRng {
+    rng: StdRng,
+}
+
+impl BattleRng {
+    pub fn from_seed(seed: u64) -> BattleRng {
+        BattleRng {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn gen_range<T, R>(&mut self, range: R) -> T
+    where
+        T: SampleUniform,
+        R: SampleRange<T>,
+    {
+        self.rng.gen_range(range)
+    }
+
+    // Returns true with the given probability (clamped to [0, 1]).
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.rng.gen::<f32>() < probability.clamp(0., 1.)
+    }
+}
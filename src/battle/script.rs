@@ -0,0 +1,176 @@
+use crate::battle::ai::{BattleView, EnemyBrain};
+use crate::battle::rng::BattleRng;
+use crate::battle::special_move;
+use crate::battle::{ActionType, Actor, Target, Team};
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope, AST, INT};
+
+// Enemy behavior written in Rhai. The script must define `fn decide(battle)` and return one of
+// the actions built by the functions registered below. The script only sees a copy of the
+// battle and can't touch anything else (no modules, no eval, bounded number of operations).
+//
+// Anything going wrong (missing file, syntax error, runtime error, bad return value) is
+// reported and the fallback brain decides instead.
+pub struct ScriptedBrain {
+    name: String,
+    engine: Engine,
+    ast: Option<AST>,
+    fallback: Box<dyn EnemyBrain>,
+}
+
+impl ScriptedBrain {
+    pub fn load(path: &str, fallback: Box<dyn EnemyBrain>) -> ScriptedBrain {
+        let engine = ScriptedBrain::sandboxed_engine();
+        let ast = match std::fs::read_to_string(path) {
+            Ok(source) => match engine.compile(&source) {
+                Ok(ast) => Some(ast),
+                Err(err) => {
+                    println!("[ERROR] Couldn't compile {}: {}", path, err);
+                    None
+                }
+            },
+            Err(err) => {
+                println!("[ERROR] Couldn't read {}: {}", path, err);
+                None
+            }
+        };
+        ScriptedBrain {
+            name: format!("script {}", path),
+            engine,
+            ast,
+            fallback,
+        }
+    }
+
+    fn sandboxed_engine() -> Engine {
+        let mut engine = Engine::new();
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.set_max_operations(50_000);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(1024);
+        engine.set_max_array_size(256);
+        engine.set_max_map_size(256);
+
+        // Targets, in absolute terms: the party is always the party.
+        engine.register_type_with_name::<Target>("Target");
+        engine.register_fn("party_member", |id: INT| {
+            Target::Single((Team::Ally, id.max(0) as usize))
+        });
+        engine.register_fn("whole_party", || Target::WholeTeam(Team::Ally));
+        engine.register_fn("enemy", |id: INT| {
+            Target::Single((Team::Enemy, id.max(0) as usize))
+        });
+        engine.register_fn("all_enemies", || Target::WholeTeam(Team::Enemy));
+
+        // Actions
+        engine.register_type_with_name::<ActionType>("Action");
+        engine.register_fn("bash", ActionType::Bash);
+        engine.register_fn("guard", || ActionType::Guard);
+        engine.register_fn(
+            "special_move",
+            |name: &str, target: Target| -> Result<ActionType, Box<EvalAltResult>> {
+                match special_move::find_by_name(name) {
+                    Some(move_id) => Ok(ActionType::SpecialMove(move_id, target)),
+                    None => Err(format!("Unknown special move: {}", name).into()),
+                }
+            },
+        );
+
+        engine
+    }
+
+    fn actor_map(actor: &Actor) -> Dynamic {
+        let (hp, max_hp) = actor.hp.current_and_max();
+        let (pp, max_pp) = actor.pp.current_and_max();
+        let mut map = Map::new();
+        map.insert("name".into(), actor.name.clone().into());
+        map.insert("hp".into(), (hp as INT).into());
+        map.insert("max_hp".into(), (max_hp as INT).into());
+        map.insert("pp".into(), (pp as INT).into());
+        map.insert("max_pp".into(), (max_pp as INT).into());
+        map.insert("alive".into(), (hp > 0).into());
+        map.insert(
+            "offense".into(),
+            (actor.stats.offense.multiplied() as INT).into(),
+        );
+        map.insert(
+            "defense".into(),
+            (actor.stats.defense.multiplied() as INT).into(),
+        );
+        map.insert(
+            "speed".into(),
+            (actor.stats.speed.multiplied() as INT).into(),
+        );
+        map.insert("iq".into(), (actor.stats.iq.multiplied() as INT).into());
+        map.insert("guts".into(), (actor.stats.guts.multiplied() as INT).into());
+        map.insert("luck".into(), (actor.stats.luck.multiplied() as INT).into());
+        let statuses: Vec<Dynamic> = actor
+            .statuses
+            .iter()
+            .map(|s| s.kind.name().into())
+            .collect();
+        map.insert("statuses".into(), statuses.into());
+        // Only what isn't normal, e.g. #{ thunder: "weak" }.
+        let mut affinities = Map::new();
+        for (element, affinity) in actor.affinities.table() {
+            affinities.insert(element.name().into(), affinity.name().into());
+        }
+        map.insert("affinities".into(), affinities.into());
+        map.into()
+    }
+
+    // Scripts don't get to roll dice themselves, they get a pre-rolled number in [0, 1) from the
+    // battle RNG instead to keep fights reproducible.
+    fn battle_map(view: &BattleView, rng: &mut BattleRng) -> Map {
+        let mut map = Map::new();
+        let allies: Vec<Dynamic> = view.allies.iter().map(ScriptedBrain::actor_map).collect();
+        let enemies: Vec<Dynamic> = view.enemies.iter().map(ScriptedBrain::actor_map).collect();
+        map.insert("party".into(), allies.into());
+        map.insert("enemies".into(), enemies.into());
+        map.insert("turn".into(), (view.turn as INT).into());
+        map.insert("me".into(), (view.me as INT).into());
+        map.insert(
+            "roll".into(),
+            (rng.gen_range(0.0..1.0) as rhai::FLOAT).into(),
+        );
+        map
+    }
+
+    // Out-of-range targets are refused here rather than in the middle of the turn.
+    fn is_valid(view: &BattleView, action: &ActionType) -> bool {
+        let target = match action {
+            ActionType::Bash(target)
+            | ActionType::SpecialMove(_, target)
+            | ActionType::Item(_, target) => target,
+            ActionType::Guard => return true,
+        };
+        match target {
+            Target::Single((Team::Ally, id)) => *id < view.allies.len(),
+            Target::Single((Team::Enemy, id)) => *id < view.enemies.len(),
+            Target::WholeTeam(_) => true,
+        }
+    }
+}
+
+impl EnemyBrain for ScriptedBrain {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decide(&mut self, view: &BattleView, rng: &mut BattleRng) -> ActionType {
+        if let Some(ast) = &self.ast {
+            let battle = ScriptedBrain::battle_map(view, rng);
+            let result =
+                self.engine
+                    .call_fn::<ActionType>(&mut Scope::new(), ast, "decide", (battle,));
+            match result {
+                Ok(action) if ScriptedBrain::is_valid(view, &action) => return action,
+                Ok(_) => println!("[ERROR] {} picked an invalid target.", self.name),
+                Err(err) => println!("[ERROR] {} failed: {}", self.name, err),
+            }
+        }
+        self.fallback.decide(view, rng)
+    }
+}
@@ -0,0 +1,307 @@
+use crate::battle::effect::Effect;
+use crate::battle::element::Element;
+use crate::battle::hit::Accuracy;
+use crate::battle::stat::StatKind;
+use crate::battle::status::StatusKind;
+use crate::battle::turn_order::Priority;
+use crate::battle::{ActorIdentifier, Target};
+
+// PSI-like moves. The catalog is static for now, actors only store indices into it.
+pub type MoveId = usize;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PowerTier {
+    Alpha,
+    Beta,
+    Gamma,
+    Omega,
+}
+
+impl PowerTier {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            PowerTier::Alpha => "α",
+            PowerTier::Beta => "β",
+            PowerTier::Gamma => "γ",
+            PowerTier::Omega => "Ω",
+        }
+    }
+}
+
+// Relative to the caster : a "SingleEnemy" move cast by an enemy aims the party.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TargetRule {
+    SingleEnemy,
+    AllEnemies,
+    SingleAlly,
+    AllAllies,
+    Caster,
+}
+
+impl TargetRule {
+    // Starting point for the target selection, the first actor of the aimed side.
+    pub fn default_target(&self, caster: &ActorIdentifier) -> Target {
+        let (caster_team, caster_id) = caster;
+        match self {
+            TargetRule::SingleEnemy => Target::Single((caster_team.opposite(), 0)),
+            TargetRule::AllEnemies => Target::WholeTeam(caster_team.opposite()),
+            TargetRule::SingleAlly => Target::Single((caster_team.clone(), 0)),
+            TargetRule::AllAllies => Target::WholeTeam(caster_team.clone()),
+            TargetRule::Caster => Target::Single((caster_team.clone(), *caster_id)),
+        }
+    }
+
+    // Whether the player gets to pick the target or not.
+    pub fn is_selectable(&self) -> bool {
+        matches!(self, TargetRule::SingleEnemy | TargetRule::SingleAlly)
+    }
+}
+
+pub struct SpecialMove {
+    pub name: &'static str,
+    pub tier: PowerTier,
+    pub pp_cost: u16,
+    pub element: Element,
+    pub target: TargetRule,
+    pub accuracy: Accuracy,
+    pub priority: Priority,
+    pub effect: Effect,
+}
+
+impl SpecialMove {
+    pub fn display_name(&self) -> String {
+        format!("{} {}", self.name, self.tier.symbol())
+    }
+}
+
+pub const CATALOG: &[SpecialMove] = &[
+    SpecialMove {
+        name: "PK Fire",
+        tier: PowerTier::Alpha,
+        pp_cost: 6,
+        element: Element::Fire,
+        target: TargetRule::AllEnemies,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::Damage(30),
+    },
+    SpecialMove {
+        name: "PK Fire",
+        tier: PowerTier::Beta,
+        pp_cost: 12,
+        element: Element::Fire,
+        target: TargetRule::AllEnemies,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Last,
+        effect: Effect::Damage(60),
+    },
+    SpecialMove {
+        name: "PK Freeze",
+        tier: PowerTier::Alpha,
+        pp_cost: 5,
+        element: Element::Freeze,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::Damage(50),
+    },
+    SpecialMove {
+        name: "PK Freeze",
+        tier: PowerTier::Beta,
+        pp_cost: 9,
+        element: Element::Freeze,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Last,
+        effect: Effect::Damage(110),
+    },
+    SpecialMove {
+        name: "PK Thunder",
+        tier: PowerTier::Alpha,
+        pp_cost: 8,
+        element: Element::Thunder,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::Damage(70),
+    },
+    SpecialMove {
+        name: "PK Flash",
+        tier: PowerTier::Omega,
+        pp_cost: 28,
+        element: Element::Flash,
+        target: TargetRule::AllEnemies,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Last,
+        effect: Effect::Damage(150),
+    },
+    SpecialMove {
+        name: "Lifeup",
+        tier: PowerTier::Alpha,
+        pp_cost: 5,
+        element: Element::Neutral,
+        target: TargetRule::SingleAlly,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::Heal(80),
+    },
+    SpecialMove {
+        name: "Lifeup",
+        tier: PowerTier::Gamma,
+        pp_cost: 24,
+        element: Element::Neutral,
+        target: TargetRule::AllAllies,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::Heal(200),
+    },
+    SpecialMove {
+        name: "Offense up",
+        tier: PowerTier::Alpha,
+        pp_cost: 10,
+        element: Element::Neutral,
+        target: TargetRule::SingleAlly,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Offense, 1),
+    },
+    SpecialMove {
+        name: "Defense down",
+        tier: PowerTier::Alpha,
+        pp_cost: 6,
+        element: Element::Neutral,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Mental(0.9),
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Defense, -1),
+    },
+    SpecialMove {
+        name: "Offense down",
+        tier: PowerTier::Alpha,
+        pp_cost: 6,
+        element: Element::Neutral,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Mental(0.9),
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Offense, -1),
+    },
+    SpecialMove {
+        name: "Defense up",
+        tier: PowerTier::Alpha,
+        pp_cost: 8,
+        element: Element::Neutral,
+        target: TargetRule::SingleAlly,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Defense, 1),
+    },
+    SpecialMove {
+        name: "Speed up",
+        tier: PowerTier::Alpha,
+        pp_cost: 6,
+        element: Element::Neutral,
+        target: TargetRule::SingleAlly,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Speed, 1),
+    },
+    SpecialMove {
+        name: "Speed down",
+        tier: PowerTier::Alpha,
+        pp_cost: 6,
+        element: Element::Neutral,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Mental(0.9),
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Speed, -1),
+    },
+    SpecialMove {
+        name: "IQ up",
+        tier: PowerTier::Alpha,
+        pp_cost: 6,
+        element: Element::Neutral,
+        target: TargetRule::SingleAlly,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Iq, 1),
+    },
+    SpecialMove {
+        name: "IQ down",
+        tier: PowerTier::Alpha,
+        pp_cost: 6,
+        element: Element::Neutral,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Mental(0.9),
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Iq, -1),
+    },
+    SpecialMove {
+        name: "Psych up",
+        tier: PowerTier::Alpha,
+        pp_cost: 4,
+        element: Element::Neutral,
+        target: TargetRule::Caster,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::Buff(StatKind::Offense, 2),
+    },
+    SpecialMove {
+        name: "Magnet",
+        tier: PowerTier::Alpha,
+        pp_cost: 0,
+        element: Element::Neutral,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Mental(0.9),
+        priority: Priority::Normal,
+        effect: Effect::DrainPp(10),
+    },
+    SpecialMove {
+        name: "Hypnosis",
+        tier: PowerTier::Alpha,
+        pp_cost: 6,
+        element: Element::Neutral,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Mental(0.75),
+        priority: Priority::Normal,
+        effect: Effect::Inflict(StatusKind::Sleep, 3),
+    },
+    SpecialMove {
+        name: "Paralysis",
+        tier: PowerTier::Alpha,
+        pp_cost: 8,
+        element: Element::Neutral,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Mental(0.75),
+        priority: Priority::Normal,
+        effect: Effect::Inflict(StatusKind::Paralysis, 3),
+    },
+    SpecialMove {
+        name: "Brainshock",
+        tier: PowerTier::Alpha,
+        pp_cost: 10,
+        element: Element::Neutral,
+        target: TargetRule::SingleEnemy,
+        accuracy: Accuracy::Mental(0.75),
+        priority: Priority::Normal,
+        effect: Effect::Inflict(StatusKind::Confusion, 3),
+    },
+    SpecialMove {
+        name: "Healing",
+        tier: PowerTier::Alpha,
+        pp_cost: 5,
+        element: Element::Neutral,
+        target: TargetRule::SingleAlly,
+        accuracy: Accuracy::Sure,
+        priority: Priority::Normal,
+        effect: Effect::CureAll,
+    },
+];
+
+pub fn get(id: MoveId) -> &'static SpecialMove {
+    &CATALOG[id]
+}
+
+// Lookup by display name ("PK Fire α")
+pub fn find_by_name(name: &str) -> Option<MoveId> {
+    CATALOG.iter().position(|m| m.display_name() == name)
+}
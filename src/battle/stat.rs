@@ -1,28 +1,85 @@
-use rand::Rng;
+use crate::battle::log::BattleLog;
+use crate::battle::rng::BattleRng;
+use serde::Deserialize;
+
+const MAX_MODIFIER: i16 = 3;
+// Turns a buff or debuff lasts, buffing again restarts the count.
+const MODIFIER_TURNS: u16 = 4;
 
 #[derive(Clone, Copy)]
 pub struct Stat {
     pub base: u16,
     pub modifier: i16,
+    // Turns before the modifier wears off, 0 without modifier.
+    pub turns_left: u16,
 }
 
 impl Stat {
     pub fn new(base: u16) -> Stat {
-        Stat { base, modifier: 0 }
+        Stat {
+            base,
+            modifier: 0,
+            turns_left: 0,
+        }
     }
     pub fn multiplied(&self) -> u16 {
         let mult: f32 = get_stat_multiplier(self.modifier) * f32::from(self.base);
         mult as u16
     }
 
+    // True when buffing by `levels` can't move the modifier any further.
+    pub fn at_limit(&self, levels: i16) -> bool {
+        (levels > 0 && self.modifier >= MAX_MODIFIER)
+            || (levels < 0 && self.modifier <= -MAX_MODIFIER)
+    }
+
     // Returns the stat difference after buff
-    pub fn buff(&mut self, levels: i16) -> i16 {
-        let current_value = self.multiplied() as i16;
-        self.modifier = i16::clamp(self.modifier + levels, -3, 3);
-        let new_value = self.multiplied() as i16;
+    pub fn buff(&mut self, levels: i16) -> i32 {
+        let current_value = i32::from(self.multiplied());
+        self.modifier = self
+            .modifier
+            .saturating_add(levels)
+            .clamp(-MAX_MODIFIER, MAX_MODIFIER);
+        self.turns_left = if self.modifier == 0 {
+            0
+        } else {
+            MODIFIER_TURNS
+        };
+        let new_value = i32::from(self.multiplied());
 
         new_value - current_value
     }
+
+    // Returns true when the modifier just wore off.
+    fn tick(&mut self) -> bool {
+        if self.turns_left == 0 {
+            return false;
+        }
+        self.turns_left -= 1;
+        if self.turns_left == 0 {
+            self.modifier = 0;
+            return true;
+        }
+        false
+    }
+}
+
+// Raw numbers, for definitions and growth computations. HP and PP are the max values.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatBlock {
+    pub hp: u16,
+    pub pp: u16,
+    pub offense: u16,
+    pub defense: u16,
+    pub speed: u16,
+    pub iq: u16,
+    #[serde(default)]
+    pub guts: u16,
+    #[serde(default)]
+    pub vitality: u16,
+    #[serde(default)]
+    pub luck: u16,
 }
 
 //Copying it for action exectuion
@@ -32,26 +89,144 @@ pub struct ActorStats {
     pub defense: Stat,
     pub speed: Stat,
     pub iq: Stat,
-    // TODO guts
+    // Critical hits and hanging on at 1 HP.
+    pub guts: Stat,
+    // Only matters for growth, see growth.rs.
+    pub vitality: Stat,
+    // Dodging and shrugging statuses off.
+    pub luck: Stat,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StatKind {
+    Offense,
+    Defense,
+    Speed,
+    Iq,
+}
+
+// The stats buffs and debuffs can touch.
+const BUFFABLE: [StatKind; 4] = [
+    StatKind::Offense,
+    StatKind::Defense,
+    StatKind::Speed,
+    StatKind::Iq,
+];
+
+impl StatKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatKind::Offense => "offense",
+            StatKind::Defense => "defense",
+            StatKind::Speed => "speed",
+            StatKind::Iq => "IQ",
+        }
+    }
+}
+
+impl ActorStats {
+    pub fn get_mut(&mut self, kind: StatKind) -> &mut Stat {
+        match kind {
+            StatKind::Offense => &mut self.offense,
+            StatKind::Defense => &mut self.defense,
+            StatKind::Speed => &mut self.speed,
+            StatKind::Iq => &mut self.iq,
+        }
+    }
+
+    // Called at the end of each turn.
+    pub fn tick_modifiers(&mut self, name: &str, log: &mut BattleLog) {
+        for kind in BUFFABLE.iter() {
+            if self.get_mut(*kind).tick() {
+                log.push(format!("{}'s {} went back to normal.", name, kind.name()));
+            }
+        }
+    }
+
+    // Nothing carries over once the battle is over.
+    pub fn reset_modifiers(&mut self) {
+        for kind in BUFFABLE.iter() {
+            let stat = self.get_mut(*kind);
+            stat.modifier = 0;
+            stat.turns_left = 0;
+        }
+    }
+}
+
+// "Two's offense went up by 12!"
+// `at_limit` comes from before the buff: small stats can round to no change without being maxed.
+pub fn describe_buff(
+    name: &str,
+    kind: StatKind,
+    levels: i16,
+    at_limit: bool,
+    delta: i32,
+) -> String {
+    match delta {
+        d if d > 0 => format!("{}'s {} went up by {}!", name, kind.name(), d),
+        d if d < 0 => format!("{}'s {} went down by {}!", name, kind.name(), -d),
+        _ if at_limit && levels > 0 => format!("{}'s {} can't go any higher.", name, kind.name()),
+        _ if at_limit => format!("{}'s {} can't go any lower.", name, kind.name()),
+        _ => format!("{}'s {} didn't visibly change.", name, kind.name()),
+    }
 }
 
 // Logic code decorrelated from structs
+// Out of range modifiers count as the closest valid one.
 fn get_stat_multiplier(modifier: i16) -> f32 {
     match modifier {
-        -3 => 0.125,
+        i16::MIN..=-3 => 0.125,
         -2 => 0.25,
         -1 => 0.5,
         0 => 1.,
         1 => 1.5,
         2 => 1.75,
-        3 => 2.,
-        _ => panic!("Invalid modifier"),
+        3..=i16::MAX => 2.,
     }
 }
 
-pub fn damage(offense: u16, attack_level: u16, defense: u16) -> u16 {
-    let mut rng = rand::thread_rng();
+// At least one bash out of twenty, whatever the guts.
+pub fn critical_chance(guts: u16) -> f32 {
+    (f32::from(guts) / 500.).max(1. / 20.)
+}
+
+// Taking mortal damage but staying at 1 HP.
+pub fn survive_chance(guts: u16) -> f32 {
+    (f32::from(guts) / 200.).min(0.25)
+}
+
+// Being faster than the attacker helps, luck always does.
+pub fn dodge_chance(speed: u16, attacker_speed: u16, luck: u16) -> f32 {
+    let speed_part = (2. * f32::from(speed) - f32::from(attacker_speed)).max(0.) / 500.;
+    (speed_part + f32::from(luck) / 400.).min(0.4)
+}
+
+pub fn status_resist_chance(luck: u16) -> f32 {
+    (f32::from(luck) / 200.).min(0.5)
+}
+
+// PSI and thrown items ignore defense, only their power and a bit of variance count.
+pub fn fixed_damage(power: u16, rng: &mut BattleRng) -> u16 {
     let random_multiplier = rng.gen_range(0.75..1.25);
-    let base = attack_level * offense - defense;
-    ((base as f32) * random_multiplier) as u16
+    ((power as f32) * random_multiplier) as u16
+}
+
+// A much faster side can catch the other off guard, up to one time out of two.
+pub fn first_strike_chance(speed: u16, other_speed: u16) -> f32 {
+    if speed <= other_speed {
+        return 0.;
+    }
+    (f32::from(speed - other_speed) / f32::from(speed)).min(0.5)
+}
+
+// Even speeds give a coin toss, and each failed attempt makes the next one easier.
+pub fn flee_chance(party_speed: u16, enemy_speed: u16, attempts: u16) -> f32 {
+    let party_speed = f32::from(party_speed);
+    let enemy_speed = f32::from(enemy_speed);
+    let base = if party_speed + enemy_speed == 0. {
+        0.5
+    } else {
+        party_speed / (party_speed + enemy_speed)
+    };
+    (base + 0.1 * f32::from(attempts)).clamp(0.05, 1.)
 }
@@ -0,0 +1,260 @@
+use crate::battle::log::BattleLog;
+use crate::battle::rng::BattleRng;
+use crate::battle::stat;
+use crate::battle::{ActionType, Actor};
+
+// Lasting conditions. Each actor has at most one status of each kind, what happens when the
+// same kind is inflicted again depends on its stacking rule.
+
+// Longest a status can be extended to.
+const MAX_TURNS: u16 = 9;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StatusKind {
+    // Loses HP at the end of each turn.
+    Poison,
+    // Can't act at all, might wake up at the start of a turn.
+    Sleep,
+    // Might be unable to move when their turn comes, slower in the turn order.
+    Paralysis,
+    // Might hit someone else than the intended target.
+    Confusion,
+    // Less accurate, see hit.rs.
+    Crying,
+    // Rolling meters go slower. The good kind of status.
+    Calm,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Stacking {
+    // Restart with the longest of both durations.
+    Refresh,
+    // Add the new duration up to MAX_TURNS.
+    Extend,
+    // Already affected, nothing happens.
+    Ignore,
+}
+
+impl StatusKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "poisoned",
+            StatusKind::Sleep => "asleep",
+            StatusKind::Paralysis => "paralyzed",
+            StatusKind::Confusion => "confused",
+            StatusKind::Crying => "crying",
+            StatusKind::Calm => "calm",
+        }
+    }
+
+    // For the cramped HUD table.
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "PSN",
+            StatusKind::Sleep => "SLP",
+            StatusKind::Paralysis => "PAR",
+            StatusKind::Confusion => "CNF",
+            StatusKind::Crying => "CRY",
+            StatusKind::Calm => "CLM",
+        }
+    }
+
+    pub fn stacking(&self) -> Stacking {
+        match self {
+            StatusKind::Poison => Stacking::Extend,
+            StatusKind::Sleep => Stacking::Ignore,
+            StatusKind::Paralysis
+            | StatusKind::Confusion
+            | StatusKind::Crying
+            | StatusKind::Calm => Stacking::Refresh,
+        }
+    }
+
+    // Lucky actors might shrug these off.
+    pub fn is_harmful(&self) -> bool {
+        !matches!(self, StatusKind::Calm)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Status {
+    pub kind: StatusKind,
+    pub turns_left: u16,
+}
+
+// What to do with the action the actor is about to take.
+pub enum ActionCheck {
+    Proceed,
+    Blocked,
+    // The turn code picks another target.
+    Confused,
+}
+
+pub fn has(actor: &Actor, kind: StatusKind) -> bool {
+    actor.statuses.iter().any(|s| s.kind == kind)
+}
+
+pub fn inflict(
+    actor: &mut Actor,
+    kind: StatusKind,
+    turns: u16,
+    rng: &mut BattleRng,
+    log: &mut BattleLog,
+) {
+    if kind.is_harmful()
+        && !has(actor, kind)
+        && rng.chance(stat::status_resist_chance(actor.stats.luck.multiplied()))
+    {
+        log.push(format!("{} wasn't {} by luck!", actor.name, kind.name()));
+        return;
+    }
+    let turns = turns.min(MAX_TURNS);
+    match actor.statuses.iter_mut().find(|s| s.kind == kind) {
+        None => {
+            actor.statuses.push(Status {
+                kind,
+                turns_left: turns,
+            });
+            log.push(format!("{} is now {}!", actor.name, kind.name()));
+        }
+        Some(status) => match kind.stacking() {
+            Stacking::Refresh => {
+                status.turns_left = status.turns_left.max(turns);
+                log.push(format!("{} is still {}.", actor.name, kind.name()));
+            }
+            Stacking::Extend => {
+                status.turns_left = (status.turns_left + turns).min(MAX_TURNS);
+                log.push(format!("{} is even more {}!", actor.name, kind.name()));
+            }
+            Stacking::Ignore => {
+                log.push(format!("{} is already {}.", actor.name, kind.name()));
+            }
+        },
+    }
+}
+
+// Returns false if there was nothing to cure.
+pub fn cure(actor: &mut Actor, kind: StatusKind, log: &mut BattleLog) -> bool {
+    let count = actor.statuses.len();
+    actor.statuses.retain(|s| s.kind != kind);
+    if actor.statuses.len() == count {
+        return false;
+    }
+    log.push(format!("{} is no longer {}.", actor.name, kind.name()));
+    true
+}
+
+pub fn cure_all(actor: &mut Actor, log: &mut BattleLog) -> bool {
+    let kinds: Vec<StatusKind> = actor.statuses.iter().map(|s| s.kind).collect();
+    for kind in kinds.iter() {
+        cure(actor, *kind, log);
+    }
+    !kinds.is_empty()
+}
+
+// Sleeping characters are skipped during the action selection.
+pub fn can_choose_action(actor: &Actor) -> bool {
+    !has(actor, StatusKind::Sleep)
+}
+
+// Hard to step aside while asleep or paralyzed.
+pub fn can_dodge(actor: &Actor) -> bool {
+    !has(actor, StatusKind::Sleep) && !has(actor, StatusKind::Paralysis)
+}
+
+pub fn turn_order_speed(actor: &Actor, speed: u16) -> u16 {
+    if has(actor, StatusKind::Paralysis) {
+        speed / 2
+    } else {
+        speed
+    }
+}
+
+// Applied on top of the battle's rolling speed.
+pub fn roll_rate_factor(actor: &Actor) -> f32 {
+    let mut factor = 1.;
+    if has(actor, StatusKind::Calm) {
+        factor *= 0.5;
+    }
+    // Poison makes everything worse.
+    if has(actor, StatusKind::Poison) {
+        factor *= 1.5;
+    }
+    factor
+}
+
+// " [asleep, poisoned]", or nothing.
+pub fn describe(actor: &Actor) -> String {
+    if actor.statuses.is_empty() {
+        return String::new();
+    }
+    let names: Vec<&str> = actor.statuses.iter().map(|s| s.kind.name()).collect();
+    format!(" [{}]", names.join(", "))
+}
+
+pub fn short_description(actor: &Actor) -> String {
+    let names: Vec<&str> = actor.statuses.iter().map(|s| s.kind.short_name()).collect();
+    names.join(" ")
+}
+
+// Hooks, called by the turn logic on living actors.
+
+pub fn on_turn_start(actor: &mut Actor, rng: &mut BattleRng, log: &mut BattleLog) {
+    if has(actor, StatusKind::Sleep) && rng.chance(0.25) {
+        log.push(format!("{} woke up!", actor.name));
+        actor.statuses.retain(|s| s.kind != StatusKind::Sleep);
+    }
+}
+
+pub fn on_action_start(
+    actor: &Actor,
+    action_type: &ActionType,
+    rng: &mut BattleRng,
+    log: &mut BattleLog,
+) -> ActionCheck {
+    if has(actor, StatusKind::Sleep) {
+        log.push(format!("{} is fast asleep.", actor.name));
+        return ActionCheck::Blocked;
+    }
+    // Guarding always works, you only have to stand there.
+    if let ActionType::Guard = action_type {
+        return ActionCheck::Proceed;
+    }
+    if has(actor, StatusKind::Paralysis) && rng.chance(0.5) {
+        log.push(format!("{} is paralyzed and can't move!", actor.name));
+        return ActionCheck::Blocked;
+    }
+    if has(actor, StatusKind::Confusion) && rng.chance(0.5) {
+        return ActionCheck::Confused;
+    }
+    ActionCheck::Proceed
+}
+
+pub fn on_turn_end(actor: &mut Actor, log: &mut BattleLog) {
+    // Nothing lingers on a K.O. actor.
+    if actor.hp.current_and_max().0 == 0 {
+        actor.statuses.clear();
+        return;
+    }
+    if has(actor, StatusKind::Poison) {
+        let damage = (actor.hp.current_and_max().1 / 10).max(1);
+        log.push(format!(
+            "{} took {} damage from the poison.",
+            actor.name, damage
+        ));
+        actor.take_damage(damage, log);
+    }
+    for status in actor.statuses.iter_mut() {
+        status.turns_left = status.turns_left.saturating_sub(1);
+    }
+    let (expired, remaining): (Vec<Status>, Vec<Status>) =
+        actor.statuses.iter().partition(|s| s.turns_left == 0);
+    actor.statuses = remaining;
+    for status in expired {
+        log.push(format!(
+            "{} is no longer {}.",
+            actor.name,
+            status.kind.name()
+        ));
+    }
+}
@@ -1,15 +1,30 @@
 use std::rc::Rc;
 
 use crate::battle::action::Action;
+use crate::battle::action::ActionContext;
 use crate::battle::action::Bash;
+use crate::battle::action::Caster;
+use crate::battle::action::Guard;
+use crate::battle::action::ItemAction;
+use crate::battle::action::KoTargetRule;
+use crate::battle::action::SpecialMoveAction;
 use crate::battle::action_decision::CharacterTurnDecisionState;
+use crate::battle::ai::{BattleView, RandomTarget};
+use crate::battle::hit::HitOutcome;
+use crate::battle::item;
+use crate::battle::log::BattleLog;
+use crate::battle::rhythm::BeatInput;
+use crate::battle::rng::BattleRng;
+use crate::battle::special_move;
+use crate::battle::status::{self, ActionCheck};
 use crate::battle::ActionType;
 use crate::battle::Actor;
 use crate::battle::ActorIdentifier;
+use crate::battle::CharacterKoSignal;
 use crate::battle::MacroBattleStates;
 use crate::battle::Target;
 use crate::battle::Team;
-use crate::battle::TurnAction;
+use crate::battle::UIAction;
 use crate::battle::{BattleState, BattleStateTransition};
 use crate::timer::Timer;
 use crate::Assets;
@@ -49,8 +64,8 @@ impl Announce {
             target,
         }
     }
-    pub fn update(&mut self, ctx: &Context) -> SubStateTransition {
-        self.time.tetra_tick(ctx);
+    pub fn update(&mut self, dt: f32) -> SubStateTransition {
+        self.time.tick(dt);
         if self.time.done() {
             println!("Going to Act!");
             return SubStateTransition::NextSubState(TurnSubState::DoIt(DoIt::new(
@@ -67,6 +82,8 @@ pub struct DoIt {
     action: Rc<dyn Action>,
     caster: ActorIdentifier,
     target: Target,
+    // Who got hit, missed or dodged, for the log and whatever gets animated later.
+    hits: Vec<(ActorIdentifier, HitOutcome)>,
 }
 
 impl DoIt {
@@ -75,6 +92,38 @@ impl DoIt {
             action,
             caster,
             target,
+            hits: vec![],
+        }
+    }
+
+    // The action reports outcomes in the order of the targeted slice.
+    fn record_hits(&mut self, outcomes: Vec<HitOutcome>) {
+        let target = self.target.clone();
+        self.hits = outcomes
+            .into_iter()
+            .enumerate()
+            .map(|(i, outcome)| match &target {
+                Target::Single(id) => (id.clone(), outcome),
+                Target::WholeTeam(team) => ((team.clone(), i), outcome),
+            })
+            .collect();
+    }
+
+    fn report_hits(
+        &self,
+        caster: &Caster,
+        allies: &[Actor],
+        enemies: &[Actor],
+        log: &mut BattleLog,
+    ) {
+        for ((team, id), outcome) in self.hits.iter() {
+            let target = match team {
+                Team::Ally => &allies[*id],
+                Team::Enemy => &enemies[*id],
+            };
+            if let Some(line) = outcome.describe(&caster.name, &target.name) {
+                log.push(line);
+            }
         }
     }
 }
@@ -96,71 +145,223 @@ impl TurnUnrollState {
         }
     }
 
-    // TODO ?
-    fn process_ally_action(scene: &BattleScene, action: TurnAction) -> SubStateTransition {
-        let ally = &scene.allies[action.id_in_team];
-        if ally.hp.current_and_max().0 == 0 {
-            println!("Skipping action because K.O.");
-            return SubStateTransition::NextSubState(TurnSubState::NextAction);
+    // Moves the turn out, leaving a fresh "next action" behind, for when it has to be put aside.
+    pub fn take(&mut self) -> TurnUnrollState {
+        TurnUnrollState {
+            sub_state: std::mem::replace(&mut self.sub_state, TurnSubState::NextAction),
         }
-        let action_record = match scene
-            .allies_actions
-            .iter()
-            .find(|rec| rec.id_in_team == action.id_in_team)
-        {
-            Some(a) => a,
-            _ => unreachable!("[ERROR] An action record should always involve a character."),
+    }
+
+    fn describe_action(scene: &BattleScene, action_type: &ActionType) -> String {
+        let target_name = |target: &Target| match target {
+            Target::Single((Team::Ally, id)) => scene.allies[*id].name.clone(),
+            Target::Single((Team::Enemy, id)) => scene.enemies[*id].name.clone(),
+            Target::WholeTeam(Team::Ally) => String::from("the party"),
+            Target::WholeTeam(Team::Enemy) => String::from("the enemies"),
         };
-        let action_str = match action_record.action_type {
-            ActionType::Bash(_) => "Bash",
-            ActionType::Psi => "PSI",
-            ActionType::Item => "Item",
-            ActionType::Guard => "Guard",
+        match action_type {
+            ActionType::Bash(target) => format!("Bash on {}", target_name(target)),
+            ActionType::SpecialMove(move_id, target) => format!(
+                "{} on {}",
+                special_move::get(*move_id).display_name(),
+                target_name(target)
+            ),
+            ActionType::Item(item_id, target) => {
+                format!("{} on {}", item::get(*item_id).name, target_name(target))
+            }
+            ActionType::Guard => String::from("Guard"),
+        }
+    }
+
+    // The brain is taken out of the actor for the time of the decision so it can look at the
+    // whole battle, its owner included.
+    pub fn enemy_decision(scene: &mut BattleScene, id: usize) -> ActionType {
+        let mut brain = match scene.enemies[id].brain.take() {
+            Some(brain) => brain,
+            None => Box::new(RandomTarget),
         };
-        println!(
-            "→ {} ({}) will act ({})",
-            ally.name, action_record.id_in_team, action_str
-        );
-        let (action, target) = match &action_record.action_type {
-            ActionType::Bash(target) => (Rc::new(Bash::new()), target.clone()),
-            _ => unimplemented!(),
+        let view = BattleView {
+            allies: &scene.allies,
+            enemies: &scene.enemies,
+            turn: scene.turn,
+            me: id,
+        };
+        let decision = brain.decide(&view, &mut scene.rng);
+        scene.log.push(format!(
+            "{} ({}) picked {}.",
+            scene.enemies[id].name,
+            brain.name(),
+            TurnUnrollState::describe_action(scene, &decision)
+        ));
+        scene.enemies[id].brain = Some(brain);
+        decision
+    }
+
+    fn process_action(
+        scene: &mut BattleScene,
+        caster: ActorIdentifier,
+        action_type: ActionType,
+    ) -> SubStateTransition {
+        let actor = match &caster {
+            (Team::Ally, id) => &mut scene.allies[*id],
+            (Team::Enemy, id) => &mut scene.enemies[*id],
+        };
+        let announcement = match &action_type {
+            ActionType::Bash(_) => format!("{} bashes!", actor.name),
+            ActionType::SpecialMove(move_id, _) => format!(
+                "{} tries {}!",
+                actor.name,
+                special_move::get(*move_id).display_name()
+            ),
+            ActionType::Item(item_id, _) => {
+                format!("{} uses the {}!", actor.name, item::get(*item_id).name)
+            }
+            ActionType::Guard => format!("{} is on guard.", actor.name),
+        };
+        scene.log.push(announcement);
+        let (action, target): (Rc<dyn Action>, Target) = match action_type {
+            ActionType::Bash(target) => (Rc::new(Bash::new(caster.0 == Team::Ally)), target),
+            ActionType::SpecialMove(move_id, target) => {
+                let pp_cost = special_move::get(move_id).pp_cost;
+                if !actor.try_spend_pp(pp_cost) {
+                    scene
+                        .log
+                        .push(format!("{} doesn't have enough PP!", actor.name));
+                    return SubStateTransition::NextSubState(TurnSubState::NextAction);
+                }
+                (Rc::new(SpecialMoveAction::new(move_id)), target)
+            }
+            ActionType::Item(item_id, target) => {
+                // Only the party has an inventory.
+                // Somebody else could have used the last one earlier in the turn.
+                if let Team::Ally = caster.0 {
+                    if !scene.inventory.remove(item_id) {
+                        scene
+                            .log
+                            .push(format!("There is no {} left!", item::get(item_id).name));
+                        return SubStateTransition::NextSubState(TurnSubState::NextAction);
+                    }
+                }
+                (Rc::new(ItemAction::new(item_id)), target)
+            }
+            ActionType::Guard => {
+                // Already set when the turn started, see TurnPreparationState.
+                actor.guarding = true;
+                (Rc::new(Guard::new()), Target::Single(caster.clone()))
+            }
         };
-        let caster = (Team::Ally, action_record.id_in_team);
         SubStateTransition::NextSubState(TurnSubState::Announce(Announce::new(
             action, caster, target,
         )))
     }
 
+    // Single targets become any living actor, team-wide ones aim the other team.
+    fn confuse(
+        scene: &mut BattleScene,
+        caster: &ActorIdentifier,
+        action_type: ActionType,
+    ) -> ActionType {
+        let living: Vec<ActorIdentifier> = scene
+            .allies
+            .iter()
+            .enumerate()
+            .map(|(i, a)| ((Team::Ally, i), a))
+            .chain(
+                scene
+                    .enemies
+                    .iter()
+                    .enumerate()
+                    .map(|(i, a)| ((Team::Enemy, i), a)),
+            )
+            .filter(|(_, a)| a.hp.current_and_max().0 > 0)
+            .map(|(id, _)| id)
+            .collect();
+        let redirect = |target: Target, rng: &mut BattleRng| match target {
+            Target::Single(_) => Target::Single(living[rng.gen_range(0..living.len())].clone()),
+            Target::WholeTeam(team) => Target::WholeTeam(team.opposite()),
+        };
+        let confused = match action_type {
+            ActionType::Bash(target) => ActionType::Bash(redirect(target, &mut scene.rng)),
+            ActionType::SpecialMove(move_id, target) => {
+                ActionType::SpecialMove(move_id, redirect(target, &mut scene.rng))
+            }
+            ActionType::Item(item_id, target) => {
+                ActionType::Item(item_id, redirect(target, &mut scene.rng))
+            }
+            ActionType::Guard => ActionType::Guard,
+        };
+        let name = match caster {
+            (Team::Ally, id) => &scene.allies[*id].name,
+            (Team::Enemy, id) => &scene.enemies[*id].name,
+        };
+        scene.log.push(format!(
+            "{} is confused and goes for {} instead!",
+            name,
+            TurnUnrollState::describe_action(scene, &confused)
+        ));
+        confused
+    }
+
     fn end_of_turn(scene: &mut BattleScene) -> SubStateTransition {
         println!("- End of Turn -");
         scene.allies_actions.clear();
+        scene.enemies_actions.clear();
+        for actor in scene.allies.iter_mut().chain(scene.enemies.iter_mut()) {
+            actor.guarding = false;
+            status::on_turn_end(actor, &mut scene.log);
+            actor.stats.tick_modifiers(&actor.name, &mut scene.log);
+        }
 
         SubStateTransition::EndOfTurn
     }
 
     fn next_action(scene: &mut BattleScene) -> SubStateTransition {
         let next_action = scene.turn_order.pop_front().unwrap();
-        match next_action.team {
-            Team::Ally => TurnUnrollState::process_ally_action(scene, next_action),
-            // TODO Enemy AI decision
+        let id = next_action.id_in_team;
+        let action_type = match next_action.team {
+            Team::Ally => {
+                if scene.allies[id].hp.current_and_max().0 == 0 {
+                    println!("Skipping action because K.O.");
+                    return SubStateTransition::NextSubState(TurnSubState::NextAction);
+                }
+                match scene.allies_actions.iter().find(|rec| rec.id_in_team == id) {
+                    Some(a) => a.action_type.clone(),
+                    _ => {
+                        unreachable!("[ERROR] An action record should always involve a character.")
+                    }
+                }
+            }
             Team::Enemy => {
-                let enemy = &scene.enemies[next_action.id_in_team];
+                let enemy = &scene.enemies[id];
                 if enemy.hp.current_and_max().0 == 0 {
-                    println!(
-                        "→ {} ({}) was previously K.O-ed. Skipping",
-                        enemy.name, next_action.id_in_team
-                    );
+                    println!("→ {} ({}) was previously K.O-ed. Skipping", enemy.name, id);
                     return SubStateTransition::NextSubState(TurnSubState::NextAction);
                 }
-                println!("→ {} ({}) will do", enemy.name, next_action.id_in_team);
-                let action = Rc::new(Bash::new());
-                SubStateTransition::NextSubState(TurnSubState::Announce(Announce::new(
-                    action,
-                    (Team::Enemy, next_action.id_in_team),
-                    Target::Single((Team::Ally, 0)),
-                )))
+                match scene
+                    .enemies_actions
+                    .iter()
+                    .find(|(enemy_id, _)| *enemy_id == id)
+                {
+                    Some((_, action_type)) => action_type.clone(),
+                    _ => unreachable!("[ERROR] Enemies decide before being put in the turn order."),
+                }
             }
-        }
+        };
+
+        let caster = (next_action.team, id);
+        let actor = match &caster {
+            (Team::Ally, id) => &scene.allies[*id],
+            (Team::Enemy, id) => &scene.enemies[*id],
+        };
+        let action_type =
+            match status::on_action_start(actor, &action_type, &mut scene.rng, &mut scene.log) {
+                ActionCheck::Proceed => action_type,
+                ActionCheck::Blocked => {
+                    return SubStateTransition::NextSubState(TurnSubState::NextAction)
+                }
+                ActionCheck::Confused => TurnUnrollState::confuse(scene, &caster, action_type),
+            };
+        TurnUnrollState::process_action(scene, caster, action_type)
     }
 
     pub fn draw(scene: &BattleScene, ctx: &mut Context, assets: &Assets) {
@@ -190,14 +391,7 @@ impl TurnUnrollState {
                 if scene.end_of_fight() {
                     Some(scene.get_end_state().unwrap())
                 } else {
-                    Some(MacroBattleStates::CharacterTurnDecision(
-                        match CharacterTurnDecisionState::new_turn(&scene.allies) {
-                            Some(a) => a,
-                            _ => unreachable!(
-                                "[ERROR] Tried to transition into a new turn with all characters K.O."
-                            ),
-                        },
-                    ))
+                    Some(CharacterTurnDecisionState::new_turn(&scene.allies))
                 }
             }
             SubStateTransition::NextSubState(sub_state) => {
@@ -211,6 +405,49 @@ impl TurnUnrollState {
         }
     }
 
+    // The single target might have fallen since the action was decided.
+    fn check_ko_target(
+        do_it: &mut DoIt,
+        allies: &[Actor],
+        enemies: &[Actor],
+        rng: &mut BattleRng,
+        log: &mut BattleLog,
+    ) -> Option<SubStateTransition> {
+        let (team, id) = match &do_it.target {
+            Target::Single(target) => target.clone(),
+            Target::WholeTeam(_) => return None,
+        };
+        let side = match team {
+            Team::Ally => allies,
+            Team::Enemy => enemies,
+        };
+        if !side[id].hp.is_ko() {
+            return None;
+        }
+        let living: Vec<usize> = side
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| !a.hp.is_ko())
+            .map(|(i, _)| i)
+            .collect();
+        match do_it.action.on_ko_target() {
+            KoTargetRule::Ignore => None,
+            KoTargetRule::Retarget if !living.is_empty() => {
+                let new_id = living[rng.gen_range(0..living.len())];
+                log.push(format!(
+                    "{} is down, {} is targeted instead.",
+                    side[id].name, side[new_id].name
+                ));
+                do_it.target = Target::Single((team, new_id));
+                None
+            }
+            _ => {
+                log.push(format!("{} is down, the action fizzled.", side[id].name));
+                Some(SubStateTransition::NextSubState(TurnSubState::NextAction))
+            }
+        }
+    }
+
     fn get_targeted_side<'a>(
         allies: &'a mut [Actor],
         enemies: &'a mut [Actor],
@@ -231,8 +468,27 @@ impl TurnUnrollState {
         }
     }
 }
+// A character falling cancels whatever they were doing.
+impl CharacterKoSignal for TurnUnrollState {
+    fn on_character_ko(&mut self, id: ActorIdentifier) {
+        let interrupted = match &mut self.sub_state {
+            TurnSubState::NextAction => false,
+            TurnSubState::Announce(announce) => announce.caster == id,
+            TurnSubState::DoIt(do_it) => {
+                if do_it.caster == id {
+                    Rc::get_mut(&mut do_it.action).unwrap().cancel();
+                }
+                false
+            }
+        };
+        if interrupted {
+            self.sub_state = TurnSubState::NextAction;
+        }
+    }
+}
+
 impl BattleState for TurnUnrollState {
-    fn update(scene: &mut BattleScene, ctx: &Context) -> BattleStateTransition {
+    fn update(scene: &mut BattleScene, dt: f32, input: &[UIAction]) -> BattleStateTransition {
         if let MacroBattleStates::TurnUnroll(state) = &mut scene.state {
             let transition = match &mut state.sub_state {
                 TurnSubState::NextAction => {
@@ -243,31 +499,63 @@ impl BattleState for TurnUnrollState {
                     }
                 }
                 // TODO Some animations and stuff?
-                TurnSubState::Announce(announce) => announce.update(ctx),
+                TurnSubState::Announce(announce) => announce.update(dt),
                 // TODO Pass around the action data
                 // TODO determine what the AI should do in their turn
                 // TODO Apply damage
-                // TODO Cancel if no more PP
                 TurnSubState::DoIt(do_it) => {
                     // Cloning the caster allows for broad selection.
                     // Think as a snapshot.
 
                     let (team, id) = &do_it.caster;
                     let id = *id;
-                    let caster_stats = match team {
-                        Team::Ally => scene.allies[id].stats.clone(),
-                        Team::Enemy => scene.enemies[id].stats.clone(),
+                    let caster = match team {
+                        Team::Ally => Caster::snapshot(&scene.allies[id]),
+                        Team::Enemy => Caster::snapshot(&scene.enemies[id]),
                     };
 
+                    if let Some(transition) = TurnUnrollState::check_ko_target(
+                        do_it,
+                        &scene.allies,
+                        &scene.enemies,
+                        &mut scene.rng,
+                        &mut scene.log,
+                    ) {
+                        return TurnUnrollState::determine_transition_from_internal_transition(
+                            transition, scene,
+                        );
+                    }
+
                     let targets = TurnUnrollState::get_targeted_side(
                         &mut scene.allies,
                         &mut scene.enemies,
                         &do_it.target,
                     );
 
-                    Rc::get_mut(&mut do_it.action)
-                        .unwrap()
-                        .go(&caster_stats, targets, ctx)
+                    let context = ActionContext {
+                        beat: BeatInput {
+                            time: scene.beat.time(),
+                            track: scene.beat.track(),
+                            config: scene.rhythm,
+                            pressed: input.contains(&UIAction::Validate),
+                        },
+                        formula: scene.formula.as_ref(),
+                    };
+                    let action = Rc::get_mut(&mut do_it.action).unwrap();
+                    let transition = action.go(
+                        &caster,
+                        targets,
+                        dt,
+                        &context,
+                        &mut scene.rng,
+                        &mut scene.log,
+                    );
+                    let outcomes = action.take_hits();
+                    if !outcomes.is_empty() {
+                        do_it.record_hits(outcomes);
+                        do_it.report_hits(&caster, &scene.allies, &scene.enemies, &mut scene.log);
+                    }
+                    transition
                 }
             };
             return TurnUnrollState::determine_transition_from_internal_transition(
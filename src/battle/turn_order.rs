@@ -0,0 +1,187 @@
+use crate::battle::item;
+use crate::battle::rng::BattleRng;
+use crate::battle::special_move;
+use crate::battle::status;
+use crate::battle::{
+    ActionType, ActorIdentifier, BattleScene, MacroBattleStates, Team, TurnAction,
+};
+
+// Who acts when within a turn: priority first, then speed with some random spread, then a fixed
+// list of tie-breakers so the same seed always gives the same order.
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Priority {
+    // Slow, heavy moves.
+    Last,
+    Normal,
+    // Guarding and quick items.
+    First,
+}
+
+pub fn priority(action: &ActionType) -> Priority {
+    match action {
+        ActionType::Guard => Priority::First,
+        ActionType::Bash(_) => Priority::Normal,
+        ActionType::SpecialMove(move_id, _) => special_move::get(*move_id).priority,
+        ActionType::Item(item_id, _) => item::get(*item_id).priority,
+    }
+}
+
+pub struct TurnOrderPolicy {
+    // Speeds are rolled within ±spread every turn, 0.2 is ±20%.
+    pub speed_spread: f32,
+}
+
+impl Default for TurnOrderPolicy {
+    fn default() -> TurnOrderPolicy {
+        TurnOrderPolicy { speed_spread: 0.2 }
+    }
+}
+
+impl TurnOrderPolicy {
+    pub fn entry(
+        &self,
+        (team, id_in_team): ActorIdentifier,
+        speed: u16,
+        priority: Priority,
+        rng: &mut BattleRng,
+    ) -> TurnAction {
+        let spread = self.speed_spread.clamp(0., 1.);
+        let factor = if spread > 0. {
+            rng.gen_range((1. - spread)..=(1. + spread))
+        } else {
+            1.
+        };
+        TurnAction {
+            id_in_team,
+            team,
+            priority,
+            speed: (f32::from(speed) * factor).round() as u16,
+            base_speed: speed,
+            tiebreak: rng.gen_range(0..=u32::MAX),
+        }
+    }
+}
+
+// Highest priority, then highest rolled speed, then highest unrolled speed, then the tiebreak
+// roll. Should all of that be equal, the party goes before the enemies and lower indices first.
+pub fn sort(order: &mut [TurnAction]) {
+    let team_rank = |team: &Team| match team {
+        Team::Ally => 0,
+        Team::Enemy => 1,
+    };
+    order.sort_by(|a, b| {
+        b.priority
+            .cmp(&a.priority)
+            .then(b.speed.cmp(&a.speed))
+            .then(b.base_speed.cmp(&a.base_speed))
+            .then(b.tiebreak.cmp(&a.tiebreak))
+            .then(team_rank(&a.team).cmp(&team_rank(&b.team)))
+            .then(a.id_in_team.cmp(&b.id_in_team))
+    });
+}
+
+// The rest of the turn while it unrolls, otherwise a guess for the coming turn without the
+// random parts. Enemies only make up their mind when the turn starts, they count as Normal.
+pub fn preview(scene: &BattleScene) -> Vec<ActorIdentifier> {
+    if let MacroBattleStates::TurnUnroll(_) = scene.state {
+        return scene
+            .turn_order
+            .iter()
+            .map(|action| (action.team.clone(), action.id_in_team))
+            .collect();
+    }
+
+    let guess = |(team, id_in_team): ActorIdentifier, speed: u16, priority: Priority| TurnAction {
+        id_in_team,
+        team,
+        priority,
+        speed,
+        base_speed: speed,
+        tiebreak: 0,
+    };
+    let mut order = vec![];
+    for record in scene.allies_actions.iter() {
+        let ally = &scene.allies[record.id_in_team];
+        let speed = status::turn_order_speed(ally, ally.stats.speed.multiplied());
+        order.push(guess(
+            (Team::Ally, record.id_in_team),
+            speed,
+            priority(&record.action_type),
+        ));
+    }
+    for (id, enemy) in scene.enemies.iter().enumerate() {
+        if !enemy.hp.is_ko() {
+            let speed = status::turn_order_speed(enemy, enemy.stats.speed.multiplied());
+            order.push(guess((Team::Enemy, id), speed, Priority::Normal));
+        }
+    }
+    sort(&mut order);
+    order
+        .into_iter()
+        .map(|action| (action.team, action.id_in_team))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(
+        (team, id_in_team): ActorIdentifier,
+        priority: Priority,
+        speed: u16,
+        base_speed: u16,
+        tiebreak: u32,
+    ) -> TurnAction {
+        TurnAction {
+            id_in_team,
+            team,
+            priority,
+            speed,
+            base_speed,
+            tiebreak,
+        }
+    }
+
+    fn ids(order: &[TurnAction]) -> Vec<ActorIdentifier> {
+        order
+            .iter()
+            .map(|action| (action.team.clone(), action.id_in_team))
+            .collect()
+    }
+
+    #[test]
+    fn sort_keys() {
+        let mut order = vec![
+            // Same as the one right below, only the team and index tell them apart.
+            action((Team::Enemy, 0), Priority::Normal, 10, 10, 5),
+            action((Team::Ally, 1), Priority::Normal, 10, 10, 5),
+            action((Team::Ally, 0), Priority::Normal, 10, 10, 5),
+            // Same speeds, the tiebreak roll decides.
+            action((Team::Enemy, 1), Priority::Normal, 10, 10, 6),
+            // Same rolled speed, faster without the roll.
+            action((Team::Enemy, 2), Priority::Normal, 10, 12, 0),
+            // The fastest roll, slowest on paper.
+            action((Team::Ally, 2), Priority::Normal, 30, 1, 0),
+            // Priority beats any speed.
+            action((Team::Enemy, 3), Priority::Last, 99, 99, 9),
+            action((Team::Ally, 3), Priority::First, 1, 1, 0),
+        ];
+        sort(&mut order);
+        // Team has no Debug, hence no assert_eq.
+        assert!(
+            ids(&order)
+                == vec![
+                    (Team::Ally, 3),
+                    (Team::Ally, 2),
+                    (Team::Enemy, 2),
+                    (Team::Enemy, 1),
+                    (Team::Ally, 0),
+                    (Team::Ally, 1),
+                    (Team::Enemy, 0),
+                    (Team::Enemy, 3),
+                ]
+        );
+    }
+}
@@ -1,7 +1,10 @@
+use crate::battle::status;
+use crate::battle::turn_order;
+use crate::battle::ActionType;
 use crate::battle::MacroBattleStates;
 use crate::battle::MacroBattleStates::TurnUnroll;
 use crate::battle::{BattleState, BattleStateTransition};
-use crate::battle::{Team, TurnAction, TurnUnrollState};
+use crate::battle::{Team, TurnUnrollState, UIAction};
 use crate::{Assets, BattleScene};
 use tetra::graphics::text::Text;
 use tetra::graphics::{Color, DrawParams};
@@ -11,32 +14,53 @@ use tetra::Context;
 pub struct TurnPreparationState;
 
 impl BattleState for TurnPreparationState {
-    fn update(scene: &mut BattleScene, _ctx: &Context) -> BattleStateTransition {
+    fn update(scene: &mut BattleScene, _dt: f32, _input: &[UIAction]) -> BattleStateTransition {
         if let MacroBattleStates::TurnPreparation(_) = &mut scene.state {
+            scene.turn += 1;
             scene.turn_order.clear();
+            for actor in scene.allies.iter_mut().chain(scene.enemies.iter_mut()) {
+                if actor.hp.current_and_max().0 > 0 {
+                    status::on_turn_start(actor, &mut scene.rng, &mut scene.log);
+                }
+            }
+            let mut order = vec![];
             for action in scene.allies_actions.iter() {
-                scene.turn_order.push_back(TurnAction {
-                    team: Team::Ally,
-                    speed: action.registered_speed,
-                    id_in_team: action.id_in_team,
-                })
+                // Guarding holds for the whole turn, not only after the actor's turn came.
+                if let ActionType::Guard = action.action_type {
+                    scene.allies[action.id_in_team].guarding = true;
+                }
+                order.push(scene.turn_order_policy.entry(
+                    (Team::Ally, action.id_in_team),
+                    status::turn_order_speed(
+                        &scene.allies[action.id_in_team],
+                        action.registered_speed,
+                    ),
+                    turn_order::priority(&action.action_type),
+                    &mut scene.rng,
+                ));
             }
-            for (id, enemy) in scene.enemies.iter().enumerate() {
-                let (current_hp, _) = enemy.hp.current_and_max();
-                if current_hp > 0 {
-                    scene.turn_order.push_back(TurnAction {
-                        id_in_team: id,
-                        team: Team::Enemy,
-                        // TODO randomized speed
-                        speed: enemy.stats.speed.multiplied(),
-                    })
+            // The party caught them off guard, enemies sit this one out.
+            let enemies_surprised = scene.first_strike.take() == Some(Team::Ally);
+            for id in 0..scene.enemies.len() {
+                if scene.enemies[id].hp.is_ko() || enemies_surprised {
+                    continue;
+                }
+                // Enemies pick their move now so it can have a priority, same as the party.
+                let action_type = TurnUnrollState::enemy_decision(scene, id);
+                let enemy = &mut scene.enemies[id];
+                if let ActionType::Guard = action_type {
+                    enemy.guarding = true;
                 }
+                order.push(scene.turn_order_policy.entry(
+                    (Team::Enemy, id),
+                    status::turn_order_speed(enemy, enemy.stats.speed.multiplied()),
+                    turn_order::priority(&action_type),
+                    &mut scene.rng,
+                ));
+                scene.enemies_actions.push((id, action_type));
             }
-            scene
-                .turn_order
-                .make_contiguous()
-                .sort_by(|a, b| b.speed.cmp(&a.speed)); // Reverse order
-            scene.turn_order.make_contiguous().reverse();
+            turn_order::sort(&mut order);
+            scene.turn_order.extend(order);
             return Some(TurnUnroll(TurnUnrollState::new()));
         }
         None
@@ -0,0 +1,112 @@
+use crate::battle::growth;
+use crate::battle::item::{self, ItemId};
+use crate::battle::{BattleScene, UIAction};
+use crate::Assets;
+use tetra::graphics::text::Text;
+use tetra::graphics::{Color, DrawParams};
+use tetra::math::Vec2;
+use tetra::Context;
+
+// What an enemy leaves behind. Characters have an empty one.
+#[derive(Clone, Default)]
+pub struct Rewards {
+    pub exp: u32,
+    pub money: u32,
+    // Item and chance to drop it, in [0, 1].
+    pub drops: Vec<(ItemId, f32)>,
+}
+
+// The spoils, for the overworld to apply. Levels are applied to the characters right away, the
+// overworld gets them back through `BattleOutcome::party`.
+#[derive(Clone)]
+pub struct BattleRewards {
+    // Indexed like the party, K.O. characters get nothing.
+    pub exp: Vec<u32>,
+    pub money: u32,
+    pub items: Vec<ItemId>,
+}
+
+// Shows the rewards one line at a time, each validation shows the next one.
+pub struct VictoryState {
+    pub rewards: BattleRewards,
+    lines: Vec<String>,
+    shown: usize,
+}
+
+impl VictoryState {
+    pub fn new(scene: &mut BattleScene) -> VictoryState {
+        let total_exp: u32 = scene.enemies.iter().map(|e| e.rewards.exp).sum();
+        let money: u32 = scene.enemies.iter().map(|e| e.rewards.money).sum();
+        let mut items = vec![];
+        for enemy in scene.enemies.iter() {
+            for &(item_id, chance) in enemy.rewards.drops.iter() {
+                if scene.rng.chance(chance) {
+                    items.push(item_id);
+                }
+            }
+        }
+
+        // Split evenly between whoever is still standing, the leftover goes to the first ones.
+        let living: Vec<usize> = scene
+            .allies
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| !a.hp.is_ko())
+            .map(|(i, _)| i)
+            .collect();
+        let mut exp = vec![0; scene.allies.len()];
+        if !living.is_empty() {
+            let share = total_exp / living.len() as u32;
+            let leftover = total_exp as usize % living.len();
+            for (rank, id) in living.iter().enumerate() {
+                exp[*id] = share + if rank < leftover { 1 } else { 0 };
+            }
+        }
+
+        let mut lines = vec![String::from("YOU WON!")];
+        for (id, gained) in exp.iter().enumerate() {
+            if *gained > 0 {
+                lines.push(format!("{} gained {} EXP.", scene.allies[id].name, gained));
+                lines.extend(growth::gain_exp(&mut scene.allies[id], *gained));
+            }
+        }
+        if money > 0 {
+            lines.push(format!("The party found ${}.", money));
+        }
+        for item_id in items.iter() {
+            lines.push(format!("The enemy dropped a {}!", item::get(*item_id).name));
+        }
+        for line in lines.iter() {
+            scene.log.push(line.clone());
+        }
+
+        VictoryState {
+            rewards: BattleRewards { exp, money, items },
+            lines,
+            shown: 1,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.shown >= self.lines.len()
+    }
+
+    pub fn update(&mut self, input: &[UIAction]) {
+        if input.contains(&UIAction::Validate) {
+            self.shown += 1;
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, assets: &Assets) {
+        let mut debug_text = Text::new("--Victory!--\n", assets.headupdaisy.clone());
+        for line in self.lines.iter().take(self.shown) {
+            debug_text.push_str(&format!("{}\n", line));
+        }
+        debug_text.draw(
+            ctx,
+            DrawParams::new()
+                .color(Color::rgb8(0xeb, 0xdb, 0xb2))
+                .position(Vec2::new(16., 360.)),
+        );
+    }
+}
@@ -1,10 +1,12 @@
 mod battle;
 mod meters;
+mod overworld;
 mod puppet;
 mod timer;
 
-use crate::battle::BattleScene;
-use puppet::{GameState, Scene, Transition};
+use crate::battle::{damage_formula, BattleScene, GameData, DATA_DIR};
+use overworld::Overworld;
+use puppet::{GameState, Scene, SceneResult, Transition};
 use tetra::graphics::text::Font;
 use tetra::graphics::Texture;
 use tetra::time::Timestep;
@@ -32,15 +34,96 @@ impl Assets {
             headupdaisy: Font::vector(ctx, "res/fonts/x14y24pxHeadUpDaisy.ttf", 24.).unwrap(),
         }
     }
+
+    // Lookup for the sprite names used in the data files.
+    pub fn sprite(&self, name: &str) -> Option<&Texture> {
+        match name {
+            "robot" => Some(&self.robot),
+            "blowharder" => Some(&self.blowharder),
+            _ => None,
+        }
+    }
+}
+
+//-- Command line --
+
+// --encounter <name> : start the given encounter from res/data/encounters.ron
+// --seed <n>         : replay a fight with the same rolls
+// --formula <name>   : damage formula, mother1, mother2 (default) or mother3
+#[derive(Default)]
+struct Options {
+    encounter: Option<String>,
+    seed: Option<u64>,
+    formula: Option<String>,
+}
+
+impl Options {
+    fn from_args() -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--encounter" => match args.next() {
+                    Some(name) => options.encounter = Some(name),
+                    None => return Err(String::from("[ERROR] --encounter needs a name")),
+                },
+                "--seed" => match args.next().map(|seed| seed.parse()) {
+                    Some(Ok(seed)) => options.seed = Some(seed),
+                    _ => return Err(String::from("[ERROR] --seed needs a positive number")),
+                },
+                "--formula" => match args.next() {
+                    Some(name) => options.formula = Some(name),
+                    None => return Err(String::from("[ERROR] --formula needs a name")),
+                },
+                _ => return Err(format!("[ERROR] Unknown argument: {}", arg)),
+            }
+        }
+        Ok(options)
+    }
 }
 
 //-- Entry point and loop --
 
 fn main() -> tetra::Result {
     println!("Hello, world!");
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(err) => {
+            println!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let seed = options.seed.unwrap_or_else(rand::random);
+    println!("Battle seed: {}", seed);
+
+    // Bad data files are better caught before opening a window.
+    let battle = match &options.encounter {
+        Some(encounter) => {
+            GameData::load(DATA_DIR).and_then(|data| data.start_encounter(encounter, seed))
+        }
+        None => BattleScene::dummy(seed),
+    };
+    let battle = match battle {
+        Ok(battle) => battle,
+        Err(err) => {
+            println!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let battle = match &options.formula {
+        Some(name) => match damage_formula(name) {
+            Some(formula) => battle.with_formula(formula),
+            None => {
+                println!("[ERROR] Unknown damage formula: {}", name);
+                std::process::exit(1);
+            }
+        },
+        None => battle,
+    };
+
     ContextBuilder::new("Hello World!", 640 * 2, 480 * 2)
         .timestep(Timestep::Fixed(60.0))
         .high_dpi(true)
         .build()?
-        .run(|ctx| GameState::new(ctx, BattleScene::dummy()))
+        .run(|ctx| GameState::new(ctx, Overworld::new(battle)))
 }
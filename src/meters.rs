@@ -12,10 +12,69 @@ impl Meter {
         }
     }
 
+    /// Where the meter is heading, what an instant meter already shows.
+    pub fn target(&self) -> u16 {
+        match self {
+            Meter::Rolling(m) => m.target_value,
+            Meter::Instant(m) => m.current_value,
+        }
+    }
+
     pub fn hit(&mut self, how_much: u16) {
         match self {
-            Meter::Rolling(m) => m.target_value = m.target_value.saturating_sub(how_much),
-            Meter::Instant(m) => m.current_value = m.current_value.saturating_sub(how_much),
+            Meter::Rolling(m) => m.hit(how_much),
+            Meter::Instant(m) => m.hit(how_much),
+        }
+    }
+
+    /// The real value reached 0, whatever the meter is rolling towards.
+    pub fn is_ko(&self) -> bool {
+        self.current_and_max().0 == 0
+    }
+
+    /// Still above 0 but rolling down to it.
+    pub fn is_mortally_wounded(&self) -> bool {
+        match self {
+            Meter::Rolling(m) => m.target_value == 0 && m.current_value > 0,
+            Meter::Instant(_) => false,
+        }
+    }
+
+    /// Stops the meter where it currently is.
+    pub fn settle(&mut self) {
+        if let Meter::Rolling(m) = self {
+            m.target_value = m.current_value;
+            m.accumulator = 0.;
+        }
+    }
+
+    pub fn heal(&mut self, how_much: u16) {
+        match self {
+            Meter::Rolling(m) => m.heal(how_much),
+            Meter::Instant(m) => m.heal(how_much),
+        }
+    }
+
+    pub fn set_max(&mut self, max: u16) {
+        match self {
+            Meter::Rolling(m) => m.set_max(max),
+            Meter::Instant(m) => m.set_max(max),
+        }
+    }
+
+    /// Returns false if the meter wasn't at 0.
+    pub fn revive_to(&mut self, value: u16) -> bool {
+        match self {
+            Meter::Rolling(m) => m.revive_to(value),
+            Meter::Instant(m) => m.revive_to(value),
+        }
+    }
+
+    /// Like `hit`, but returns how much was actually taken.
+    pub fn drain(&mut self, how_much: u16) -> u16 {
+        match self {
+            Meter::Rolling(m) => m.drain(how_much),
+            Meter::Instant(m) => m.drain(how_much),
         }
     }
 }
@@ -31,6 +90,33 @@ impl InstantMeter {
     pub fn new(current_value: u16, max: u16) -> InstantMeter {
         InstantMeter { current_value, max }
     }
+
+    pub fn hit(&mut self, how_much: u16) {
+        self.current_value = self.current_value.saturating_sub(how_much);
+    }
+
+    pub fn heal(&mut self, how_much: u16) {
+        self.current_value = self.current_value.saturating_add(how_much).min(self.max);
+    }
+
+    pub fn set_max(&mut self, max: u16) {
+        self.max = max;
+        self.current_value = self.current_value.min(max);
+    }
+
+    pub fn revive_to(&mut self, value: u16) -> bool {
+        if self.current_value > 0 || self.max == 0 {
+            return false;
+        }
+        self.current_value = value.clamp(1, self.max);
+        true
+    }
+
+    pub fn drain(&mut self, how_much: u16) -> u16 {
+        let drained = how_much.min(self.current_value);
+        self.current_value -= drained;
+        drained
+    }
 }
 
 /// One of the two not-so-unique selling points of this battle engine
@@ -56,6 +142,44 @@ impl RollingMeter {
         }
     }
 
+    pub fn hit(&mut self, how_much: u16) {
+        self.target_value = self.target_value.saturating_sub(how_much);
+        self.retargeted();
+    }
+
+    /// Also works while rolling: the meter simply heads towards the new target.
+    pub fn heal(&mut self, how_much: u16) {
+        self.target_value = self.target_value.saturating_add(how_much).min(self.max);
+        self.retargeted();
+    }
+
+    pub fn set_max(&mut self, max: u16) {
+        self.max = max;
+        self.target_value = self.target_value.min(max);
+        self.current_value = self.current_value.min(max);
+        self.retargeted();
+    }
+
+    /// Reviving doesn't roll, the character is back on their feet right away.
+    pub fn revive_to(&mut self, value: u16) -> bool {
+        if self.current_value > 0 || self.max == 0 {
+            return false;
+        }
+        self.current_value = value.clamp(1, self.max);
+        self.target_value = self.current_value;
+        self.accumulator = 0.;
+        self.check_invariants();
+        true
+    }
+
+    /// Drains from where the meter is heading, not from what's displayed.
+    pub fn drain(&mut self, how_much: u16) -> u16 {
+        let drained = how_much.min(self.target_value);
+        self.target_value -= drained;
+        self.retargeted();
+        drained
+    }
+
     fn current_rate(&self) -> f32 {
         self.base_rate * self.rate_multiplier
     }
@@ -66,26 +190,106 @@ impl RollingMeter {
         }
 
         self.accumulator += dt * self.current_rate();
-        let previous_value = self.current_value;
         if self.accumulator >= 1. {
-            let integer_part = self.accumulator.floor() as u16;
-            self.current_value = {
-                if self.current_value < self.target_value {
-                    self.current_value + integer_part
-                } else {
-                    self.current_value - integer_part
-                }
-            };
+            let integer_part = self.accumulator.floor().min(f32::from(u16::MAX)) as u16;
             self.accumulator = self.accumulator.fract();
+            // Never go past the target, fix it instead of complexifying the accumulator bit.
+            if self.current_value < self.target_value {
+                self.current_value = self
+                    .current_value
+                    .saturating_add(integer_part)
+                    .min(self.target_value);
+            } else {
+                self.current_value = self
+                    .current_value
+                    .saturating_sub(integer_part)
+                    .max(self.target_value);
+            }
+            if self.current_value == self.target_value {
+                self.accumulator = 0.;
+            }
         }
+        self.check_invariants();
+    }
 
-        // If the counter went past, fix it instead of complexifying the accumulator bit.
-        if previous_value < self.target_value && self.current_value > self.target_value {
-            self.current_value = self.target_value;
-            self.accumulator = 0.;
-        } else if previous_value > self.target_value && self.current_value < self.target_value {
-            self.current_value = self.target_value;
+    // A leftover fraction would make the next roll start with a jump.
+    fn retargeted(&mut self) {
+        if self.current_value == self.target_value {
             self.accumulator = 0.;
         }
+        self.check_invariants();
+    }
+
+    // Anything that moves the meter must keep these true.
+    fn check_invariants(&self) {
+        debug_assert!(self.current_value <= self.max);
+        debug_assert!(self.target_value <= self.max);
+        debug_assert!((0. ..1.).contains(&self.accumulator));
+        debug_assert!(self.current_value != self.target_value || self.accumulator == 0.);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn assert_sane(meter: &RollingMeter) {
+        assert!(meter.current_value <= meter.max);
+        assert!(meter.target_value <= meter.max);
+        assert!((0. ..1.).contains(&meter.accumulator));
+    }
+
+    // Random mixes of everything that can happen to a meter, rolling in between.
+    #[test]
+    fn rolling_meter_never_overshoots() {
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let max = rng.gen_range(1..=999);
+            let mut meter = RollingMeter::new(rng.gen_range(0..=max), max);
+            meter.base_rate = rng.gen_range(1. ..60.);
+            for _ in 0..500 {
+                match rng.gen_range(0..6) {
+                    0 => meter.hit(rng.gen_range(0..=meter.max.saturating_add(50))),
+                    1 => meter.heal(rng.gen_range(0..=meter.max.saturating_add(50))),
+                    2 => {
+                        meter.drain(rng.gen_range(0..=meter.max));
+                    }
+                    3 => meter.set_max(rng.gen_range(1..=999)),
+                    4 => {
+                        meter.revive_to(rng.gen_range(0..=meter.max));
+                    }
+                    _ => meter.rate_multiplier = rng.gen_range(0.25..4.),
+                }
+                assert_sane(&meter);
+
+                for _ in 0..rng.gen_range(1..10) {
+                    let (before, target) = (meter.current_value, meter.target_value);
+                    meter.update(rng.gen_range(0. ..0.5));
+                    assert_sane(&meter);
+                    assert_eq!(meter.target_value, target);
+                    if before <= target {
+                        assert!(before <= meter.current_value && meter.current_value <= target);
+                    } else {
+                        assert!(target <= meter.current_value && meter.current_value <= before);
+                    }
+                }
+            }
+        }
+    }
+
+    // Huge steps at once still stop right on the target.
+    #[test]
+    fn rolling_meter_big_steps_land_on_target() {
+        let mut meter = RollingMeter::new(500, 999);
+        meter.hit(400);
+        meter.update(f32::MAX);
+        assert_eq!(meter.current_value, 100);
+        assert_eq!(meter.accumulator, 0.);
+        meter.heal(u16::MAX);
+        meter.update(1e9);
+        assert_eq!(meter.current_value, 999);
+        assert_sane(&meter);
     }
 }
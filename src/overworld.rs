@@ -0,0 +1,104 @@
+use crate::battle::{BattleEnding, BattleScene, Inventory, PartyMember};
+use crate::puppet::{Scene, SceneResult, Transition};
+use crate::Assets;
+use tetra::graphics::text::Text;
+use tetra::graphics::{self, Color, DrawParams};
+use tetra::input::{is_key_pressed, Key};
+use tetra::math::Vec2;
+use tetra::Context;
+
+// Stands in for the overworld until there is one: starts the battle, then shows how it went.
+pub struct Overworld {
+    // Pushed on the first update.
+    battle: Option<BattleScene>,
+    report: Vec<String>,
+    money: u32,
+    // What the party picked up after battles.
+    inventory: Inventory,
+    // Kept between battles, with whatever levels they gained.
+    party: Vec<PartyMember>,
+}
+
+impl Overworld {
+    pub fn new(battle: BattleScene) -> Overworld {
+        Overworld {
+            party: battle.party(),
+            battle: Some(battle),
+            report: vec![],
+            money: 0,
+            inventory: Inventory::default(),
+        }
+    }
+}
+
+impl Scene for Overworld {
+    fn update(&mut self, ctx: &mut Context, _assets: &Assets) -> tetra::Result<Transition> {
+        if let Some(battle) = self.battle.take() {
+            return Ok(Transition::Push(Box::new(battle)));
+        }
+        // Nothing else to do here yet, leaving the overworld quits the game.
+        if is_key_pressed(ctx, Key::Enter) {
+            return Ok(Transition::Pop);
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, ctx: &mut Context, assets: &Assets) -> tetra::Result<()> {
+        graphics::clear(ctx, Color::rgb8(0x28, 0x28, 0x28));
+        let mut text = Text::new("--Overworld--\n", assets.headupdaisy.clone());
+        for line in self.report.iter() {
+            text.push_str(&format!("{}\n", line));
+        }
+        text.push('\n');
+        for member in self.party.iter() {
+            text.push_str(&format!(
+                "{}: level {}, {} EXP\n",
+                member.name, member.progression.level, member.progression.exp
+            ));
+        }
+        text.push_str(&format!("\nMoney: ${}\n", self.money));
+        if !self.inventory.is_empty() {
+            text.push_str(&format!("Found: {}\n", self.inventory.describe()));
+        }
+        text.push_str("\nPress Enter to quit.");
+        text.draw(
+            ctx,
+            DrawParams::new()
+                .color(Color::rgb8(0xeb, 0xdb, 0xb2))
+                .position(Vec2::new(16., 16.)),
+        );
+        Ok(())
+    }
+
+    fn resume(&mut self, result: SceneResult) {
+        match result {
+            SceneResult::Battle(outcome) => {
+                let line = match outcome.ending {
+                    BattleEnding::Victory => "The party won the battle.",
+                    BattleEnding::Defeat => "The party was defeated...",
+                    BattleEnding::Fled => "The party ran away from the battle.",
+                };
+                self.report.push(String::from(line));
+
+                if let Some(rewards) = outcome.rewards {
+                    let exp: u32 = rewards.exp.iter().sum();
+                    self.report.push(format!("The party earned {} EXP.", exp));
+                    self.money = self.money.saturating_add(rewards.money);
+                    for item_id in rewards.items {
+                        self.inventory.add(item_id, 1);
+                    }
+                }
+
+                for (before, after) in self.party.iter().zip(outcome.party.iter()) {
+                    if after.progression.level > before.progression.level {
+                        self.report.push(format!(
+                            "{} is now level {}.",
+                            after.name, after.progression.level
+                        ));
+                    }
+                }
+                self.party = outcome.party;
+            }
+        }
+    }
+}
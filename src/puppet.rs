@@ -1,3 +1,4 @@
+use crate::battle::BattleOutcome;
 use crate::Assets;
 use tetra::graphics::scaling::{ScalingMode, ScreenScaler};
 use tetra::graphics::{self, Color};
@@ -12,11 +13,19 @@ pub enum Transition {
     Push(Box<dyn Scene>),
     Switch(Box<dyn Scene>), // Pop + push
     Pop,
+    PopWith(SceneResult), // Pop + hand the result to the scene below
+}
+
+// What a finished scene reports to the one that pushed it.
+pub enum SceneResult {
+    Battle(BattleOutcome),
 }
 
 pub trait Scene {
     fn update(&mut self, ctx: &mut Context, assets: &Assets) -> tetra::Result<Transition>;
     fn draw(&mut self, ctx: &mut Context, assets: &Assets) -> tetra::Result<()>;
+    // Called when the scene above popped itself with a result.
+    fn resume(&mut self, _result: SceneResult) {}
 }
 
 pub struct GameState {
@@ -59,6 +68,12 @@ impl State for GameState {
                 Transition::Pop => {
                     self.scenes.pop();
                 }
+                Transition::PopWith(result) => {
+                    self.scenes.pop();
+                    if let Some(below) = self.scenes.last_mut() {
+                        below.resume(result);
+                    }
+                }
             },
             None => window::quit(ctx),
         }